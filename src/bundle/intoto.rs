@@ -0,0 +1,101 @@
+// Copyright 2024 The Sigstore Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! In-toto attestations, carried in bundles as [DSSE] envelopes.
+//!
+//! [DSSE]: https://github.com/secure-systems-lab/dsse/blob/master/protocol.md
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// The DSSE payload type of in-toto statements.
+pub const PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+
+/// The `_type` of in-toto v1 statements.
+pub const STATEMENT_TYPE_V1: &str = "https://in-toto.io/Statement/v1";
+
+/// The `_type` of in-toto v0.1 statements, still produced by some tools.
+pub const STATEMENT_TYPE_V0_1: &str = "https://in-toto.io/Statement/v0.1";
+
+/// An in-toto [Statement]: a set of subjects (usually artifacts, identified by their digests)
+/// and a typed predicate about them.
+///
+/// [Statement]: https://github.com/in-toto/attestation/blob/main/spec/v1/statement.md
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Statement {
+    #[serde(rename = "_type")]
+    pub type_: String,
+    pub subject: Vec<Subject>,
+    pub predicate_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub predicate: Option<serde_json::Value>,
+}
+
+/// A subject of an in-toto [`Statement`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Subject {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    /// Digests of the subject, keyed by algorithm (e.g. `sha256`) and hex-encoded.
+    pub digest: BTreeMap<String, String>,
+}
+
+/// Computes the DSSE Pre-Authentication Encoding (PAE) of a payload, which is what DSSE
+/// signatures are computed over.
+pub(crate) fn pae(payload_type: &str, payload: &[u8]) -> Vec<u8> {
+    let mut pae = format!(
+        "DSSEv1 {} {} {} ",
+        payload_type.len(),
+        payload_type,
+        payload.len()
+    )
+    .into_bytes();
+    pae.extend_from_slice(payload);
+    pae
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pae_encoding() {
+        // https://github.com/secure-systems-lab/dsse/blob/master/protocol.md#test-cases
+        assert_eq!(
+            pae("http://example.com/HelloWorld", b"hello world"),
+            b"DSSEv1 29 http://example.com/HelloWorld 11 hello world"
+        );
+    }
+
+    #[test]
+    fn statement_roundtrip() {
+        let statement = r#"{
+            "_type": "https://in-toto.io/Statement/v1",
+            "subject": [{"name": "a.out", "digest": {"sha256": "abcd"}}],
+            "predicateType": "https://slsa.dev/provenance/v1",
+            "predicate": {"buildDefinition": {}}
+        }"#;
+        let statement: Statement = serde_json::from_str(statement).expect("invalid statement");
+
+        assert_eq!(statement.type_, STATEMENT_TYPE_V1);
+        assert_eq!(statement.subject[0].name, "a.out");
+        assert_eq!(statement.subject[0].digest["sha256"], "abcd");
+        assert_eq!(
+            serde_json::from_value::<Statement>(serde_json::to_value(&statement).unwrap()).unwrap(),
+            statement
+        );
+    }
+}
@@ -16,7 +16,10 @@
 
 pub use sigstore_protobuf_specs::dev::sigstore::bundle::v1::Bundle;
 
+pub mod intoto;
+
 mod models;
+pub use models::Version;
 
 #[cfg(feature = "sign")]
 pub mod sign;
@@ -1,23 +1,28 @@
 use std::fmt::Display;
+use std::io;
 use std::str::FromStr;
 
 use base64::{engine::general_purpose::STANDARD as base64, Engine as _};
-use json_syntax::Print;
-
+use const_oid::{
+    db::rfc5912::{ID_SHA_256, ID_SHA_384, ID_SHA_512},
+    ObjectIdentifier,
+};
+use sha2::{Digest, Sha256, Sha384, Sha512};
 use sigstore_protobuf_specs::dev::sigstore::{
-    common::v1::LogId,
+    common::v1::{HashAlgorithm, LogId},
     rekor::v1::{Checkpoint, InclusionPromise, InclusionProof, KindVersion, TransparencyLogEntry},
 };
 
 use crate::rekor::models::{
-    log_entry::InclusionProof as RekorInclusionProof, LogEntry as RekorLogEntry,
+    hashedrekord, log_entry::InclusionProof as RekorInclusionProof, LogEntry as RekorLogEntry,
 };
 
 // Known Sigstore bundle media types.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Version {
     Bundle0_1,
     Bundle0_2,
+    Bundle0_3,
 }
 
 impl Display for Version {
@@ -25,6 +30,7 @@ impl Display for Version {
         f.write_str(match &self {
             Version::Bundle0_1 => "application/vnd.dev.sigstore.bundle+json;version=0.1",
             Version::Bundle0_2 => "application/vnd.dev.sigstore.bundle+json;version=0.2",
+            Version::Bundle0_3 => "application/vnd.dev.sigstore.bundle.v0.3+json",
         })
     }
 }
@@ -36,11 +42,109 @@ impl FromStr for Version {
         match s {
             "application/vnd.dev.sigstore.bundle+json;version=0.1" => Ok(Version::Bundle0_1),
             "application/vnd.dev.sigstore.bundle+json;version=0.2" => Ok(Version::Bundle0_2),
+            // Some clients emitted v0.3 bundles with the legacy media type format.
+            "application/vnd.dev.sigstore.bundle.v0.3+json"
+            | "application/vnd.dev.sigstore.bundle+json;version=0.3" => Ok(Version::Bundle0_3),
             _ => Err(()),
         }
     }
 }
 
+/// The hash functions message digests are signed with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DigestAlgorithm {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl DigestAlgorithm {
+    /// Returns the algorithm of a bundle's message digest, if it is supported.
+    pub(crate) fn from_bundle_algorithm(algorithm: HashAlgorithm) -> Option<Self> {
+        match algorithm {
+            HashAlgorithm::Sha2256 => Some(Self::Sha256),
+            HashAlgorithm::Sha2384 => Some(Self::Sha384),
+            HashAlgorithm::Sha2512 => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+
+    /// Returns the algorithm identified by `oid`, if it is supported.
+    pub(crate) fn from_oid(oid: ObjectIdentifier) -> Option<Self> {
+        match oid {
+            ID_SHA_256 => Some(Self::Sha256),
+            ID_SHA_384 => Some(Self::Sha384),
+            ID_SHA_512 => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn rekor_algorithm(self) -> hashedrekord::AlgorithmKind {
+        match self {
+            Self::Sha256 => hashedrekord::AlgorithmKind::sha256,
+            Self::Sha384 => hashedrekord::AlgorithmKind::sha384,
+            Self::Sha512 => hashedrekord::AlgorithmKind::sha512,
+        }
+    }
+
+    pub(crate) fn bundle_algorithm(self) -> HashAlgorithm {
+        match self {
+            Self::Sha256 => HashAlgorithm::Sha2256,
+            Self::Sha384 => HashAlgorithm::Sha2384,
+            Self::Sha512 => HashAlgorithm::Sha2512,
+        }
+    }
+}
+
+/// The digest of a signed message.
+pub(crate) struct MessageDigest {
+    pub(crate) algorithm: DigestAlgorithm,
+    pub(crate) digest: Vec<u8>,
+}
+
+/// A streaming hasher of messages.
+pub(crate) enum MessageHasher {
+    Sha256(Sha256),
+    Sha384(Sha384),
+    Sha512(Sha512),
+}
+
+impl MessageHasher {
+    /// Creates a hasher using the hash function `algorithm`.
+    pub(crate) fn from_algorithm(algorithm: DigestAlgorithm) -> Self {
+        match algorithm {
+            DigestAlgorithm::Sha256 => Self::Sha256(Sha256::new()),
+            DigestAlgorithm::Sha384 => Self::Sha384(Sha384::new()),
+            DigestAlgorithm::Sha512 => Self::Sha512(Sha512::new()),
+        }
+    }
+
+    pub(crate) fn finalize(self) -> MessageDigest {
+        let (algorithm, digest) = match self {
+            Self::Sha256(hasher) => (DigestAlgorithm::Sha256, hasher.finalize().to_vec()),
+            Self::Sha384(hasher) => (DigestAlgorithm::Sha384, hasher.finalize().to_vec()),
+            Self::Sha512(hasher) => (DigestAlgorithm::Sha512, hasher.finalize().to_vec()),
+        };
+
+        MessageDigest { algorithm, digest }
+    }
+}
+
+impl io::Write for MessageHasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Sha256(hasher) => hasher.update(buf),
+            Self::Sha384(hasher) => hasher.update(buf),
+            Self::Sha512(hasher) => hasher.update(buf),
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 #[inline]
 fn decode_hex<S: AsRef<str>>(hex: S) -> Result<Vec<u8>, ()> {
     hex::decode(hex.as_ref()).or(Err(()))
@@ -73,10 +177,14 @@ impl TryFrom<RekorLogEntry> for TransparencyLogEntry {
     type Error = ();
 
     fn try_from(value: RekorLogEntry) -> Result<Self, Self::Error> {
-        let canonicalized_body = {
-            let mut body = json_syntax::to_value(value.body).or(Err(()))?;
-            body.canonicalize();
-            body.compact_print().to_string().into_bytes()
+        let canonicalized_body = value.canonicalized_body().or(Err(()))?;
+        let body = serde_json::to_value(value.body).or(Err(()))?;
+        let kind_version = match (body["kind"].as_str(), body["apiVersion"].as_str()) {
+            (Some(kind), Some(version)) => KindVersion {
+                kind: kind.to_owned(),
+                version: version.to_owned(),
+            },
+            _ => return Err(()),
         };
         let inclusion_promise = Some(InclusionPromise {
             signed_entry_timestamp: base64
@@ -94,10 +202,7 @@ impl TryFrom<RekorLogEntry> for TransparencyLogEntry {
             inclusion_promise,
             inclusion_proof,
             integrated_time: value.integrated_time,
-            kind_version: Some(KindVersion {
-                kind: "hashedrekord".to_owned(),
-                version: "0.0.1".to_owned(),
-            }),
+            kind_version: Some(kind_version),
             log_id: Some(LogId {
                 key_id: decode_hex(value.log_i_d)?,
             }),
@@ -105,3 +210,47 @@ impl TryFrom<RekorLogEntry> for TransparencyLogEntry {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rekor::models::{log_entry::Body, DsseAllOf};
+
+    #[test]
+    fn version_media_types() {
+        for version in [Version::Bundle0_1, Version::Bundle0_2, Version::Bundle0_3] {
+            assert_eq!(version.to_string().parse(), Ok(version));
+        }
+        assert_eq!(
+            "application/vnd.dev.sigstore.bundle+json;version=0.3".parse(),
+            Ok(Version::Bundle0_3)
+        );
+        assert_eq!(
+            "application/vnd.dev.sigstore.bundle+json;version=0.4".parse::<Version>(),
+            Err(())
+        );
+    }
+
+    #[test]
+    fn log_entry_kind_version() {
+        let log_entry = RekorLogEntry {
+            body: Body::dsse(DsseAllOf::new("0.0.1".into(), Default::default())),
+            log_i_d: "00".into(),
+            verification: Default::default(),
+            ..Default::default()
+        };
+        let entry: TransparencyLogEntry = log_entry.try_into().expect("conversion failed");
+
+        assert_eq!(
+            entry.kind_version,
+            Some(KindVersion {
+                kind: "dsse".into(),
+                version: "0.0.1".into(),
+            })
+        );
+        assert_eq!(
+            entry.canonicalized_body,
+            br#"{"apiVersion":"0.0.1","kind":"dsse","spec":{}}"#
+        );
+    }
+}
@@ -14,71 +14,122 @@
 
 //! Types for signing artifacts and producing Sigstore bundles.
 
+use std::future::Future;
 use std::io::{self, Read};
-use std::time::SystemTime;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, SystemTime};
 
 use base64::{engine::general_purpose::STANDARD as base64, Engine as _};
+use const_oid::db::{rfc5912, rfc8410};
+use futures::stream::{self, StreamExt};
 use hex;
-use p256::NistP256;
-use pkcs8::der::{Encode, EncodePem};
-use sha2::{Digest, Sha256};
-use signature::DigestSigner;
+use pkcs8::der::asn1::{BitString, Null};
+use pkcs8::der::{Decode, Encode, EncodePem};
+use reqwest::StatusCode;
+use rsa::pss::get_default_pss_signature_algo_id;
+use serde_json::json;
+use sha2::{Digest, Sha256, Sha384, Sha512};
 use sigstore_protobuf_specs::dev::sigstore::bundle::v1::bundle;
 use sigstore_protobuf_specs::dev::sigstore::bundle::v1::{
-    verification_material, Bundle, VerificationMaterial,
+    verification_material, Bundle, TimestampVerificationData, VerificationMaterial,
 };
 use sigstore_protobuf_specs::dev::sigstore::common::v1::{
-    HashAlgorithm, HashOutput, MessageSignature, X509Certificate, X509CertificateChain,
+    HashOutput, MessageSignature, PublicKeyIdentifier, Rfc3161SignedTimestamp, X509Certificate,
+    X509CertificateChain,
 };
 use sigstore_protobuf_specs::dev::sigstore::rekor::v1::TransparencyLogEntry;
+use sigstore_protobuf_specs::io::intoto::{Envelope, Signature as DsseSignature};
 use tokio::io::AsyncRead;
+use tokio::sync::Mutex as AsyncMutex;
 use tokio_util::io::SyncIoBridge;
+use tracing::debug;
 use url::Url;
 use x509_cert::attr::{AttributeTypeAndValue, AttributeValue};
-use x509_cert::builder::{Builder, RequestBuilder as CertRequestBuilder};
-use x509_cert::ext::pkix as x509_ext;
+use x509_cert::ext::{pkix as x509_ext, AsExtension};
+use x509_cert::name::Name;
+use x509_cert::request::{CertReq, CertReqInfo, ExtensionReq, Version as CertReqVersion};
+use x509_cert::spki::{self, AlgorithmIdentifierOwned, SubjectPublicKeyInfoOwned};
 
-use crate::bundle::models::Version;
+use crate::bundle::intoto::{self, Statement};
+use crate::bundle::models::{DigestAlgorithm, MessageDigest, MessageHasher};
+use crate::bundle::Version;
+use crate::crypto::keyring::{key_id, Keyring};
+use crate::crypto::timestamp::{check_timestamp_response, timestamp_request};
+use crate::crypto::transparency::{verify_detached_sct, verify_embedded_sct};
+use crate::crypto::{SigStoreSigner, SigningScheme};
 use crate::errors::{Result as SigstoreResult, SigstoreError};
 use crate::fulcio::oauth::OauthTokenProvider;
 use crate::fulcio::{self, FulcioClient, FULCIO_ROOT};
-use crate::oauth::IdentityToken;
+use crate::oauth::{IdentityToken, IdentityTokenSource};
 use crate::rekor::apis::configuration::Configuration as RekorConfiguration;
-use crate::rekor::apis::entries_api::create_log_entry;
-use crate::rekor::models::{hashedrekord, proposed_entry::ProposedEntry as ProposedLogEntry};
+use crate::rekor::client::{RekorClient, RekorError};
+use crate::rekor::models::{
+    dsse, hashedrekord, log_entry::Body, proposed_entry::ProposedEntry as ProposedLogEntry,
+};
+#[cfg(feature = "sigstore-trust-root")]
+use crate::trust::sigstore::SigstoreTrustRoot;
+use crate::trust::{ServiceSelector, SigningConfig, TrustRoot};
 
 /// An asynchronous Sigstore signing session.
 ///
 /// Sessions hold a provided user identity and key materials tied to that identity. A single
 /// session may be used to sign multiple items. For more information, see [`SigningSession::sign`].
 ///
+/// Sessions created with [`SigningContext::refreshing_signer`] hold an [`IdentityTokenSource`],
+/// and renew their identity and key materials shortly before they expire.
+///
 /// This signing session operates asynchronously. To construct a synchronous [`blocking::SigningSession`],
 /// use [`SigningContext::blocking_signer()`].
 pub struct SigningSession<'ctx> {
     context: &'ctx SigningContext,
+    token_source: Option<Box<dyn IdentityTokenSource>>,
+    identity: StdMutex<Arc<SigningIdentity>>,
+    // Serializes refreshes, so that concurrent signing operations share a single new identity.
+    refresh: AsyncMutex<()>,
+}
+
+/// The identity of a [`SigningSession`]: an identity token, and the ephemeral key and Fulcio
+/// certificate tied to it.
+struct SigningIdentity {
     identity_token: IdentityToken,
-    private_key: ecdsa::SigningKey<NistP256>,
+    signer: SigStoreSigner,
     certs: fulcio::CertificateResponse,
 }
 
-impl<'ctx> SigningSession<'ctx> {
-    async fn new(
-        context: &'ctx SigningContext,
-        identity_token: IdentityToken,
-    ) -> SigstoreResult<SigningSession<'ctx>> {
-        let (private_key, certs) = Self::materials(&context.fulcio, &identity_token).await?;
+impl SigningIdentity {
+    async fn new(context: &SigningContext, identity_token: IdentityToken) -> SigstoreResult<Self> {
+        let ctfe_keyring = match &context.sct_verification {
+            SctVerification::Unconfigured => return Err(SigstoreError::CtfeKeyringMissingError),
+            SctVerification::Keyring(ctfe_keyring) => Some(ctfe_keyring),
+            SctVerification::Disabled => None,
+        };
+
+        let (signer, certs) = Self::materials(context, &identity_token).await?;
+
+        // Verify the SCT issued for our certificate, either detached or embedded in it.
+        if let Some(ctfe_keyring) = ctfe_keyring {
+            match &certs.detached_sct {
+                Some(detached_sct) => verify_detached_sct(
+                    &certs.cert,
+                    &detached_sct.signed_certificate_timestamp,
+                    ctfe_keyring,
+                ),
+                None => verify_embedded_sct(&certs.cert, &certs.chain, ctfe_keyring),
+            }
+            .map_err(Box::new)?;
+        }
+
         Ok(Self {
-            context,
             identity_token,
-            private_key,
+            signer,
             certs,
         })
     }
 
     async fn materials(
-        fulcio: &FulcioClient,
+        context: &SigningContext,
         token: &IdentityToken,
-    ) -> SigstoreResult<(ecdsa::SigningKey<NistP256>, fulcio::CertificateResponse)> {
+    ) -> SigstoreResult<(SigStoreSigner, fulcio::CertificateResponse)> {
         let subject =
                 // SEQUENCE OF RelativeDistinguishedName
                 vec![
@@ -95,23 +146,20 @@ impl<'ctx> SigningSession<'ctx> {
                     ].try_into()?
                 ].into();
 
-        let mut rng = rand::thread_rng();
-        let private_key = ecdsa::SigningKey::from(p256::SecretKey::random(&mut rng));
-        let mut builder = CertRequestBuilder::new(subject, &private_key)?;
-        builder.add_extension(&x509_ext::BasicConstraints {
-            ca: false,
-            path_len_constraint: None,
-        })?;
+        let signer = context.signing_scheme.create_signer()?;
+        let cert_req = cert_request(&signer, subject)?;
 
-        let cert_req = builder.build::<p256::ecdsa::DerSignature>()?;
-        Ok((private_key, fulcio.request_cert_v2(cert_req, token).await?))
+        Ok((
+            signer,
+            context.fulcio.request_cert_v2(cert_req, token).await?,
+        ))
     }
 
-    /// Check if the session's identity token or key material is expired.
+    /// Checks if the certificate expires within `margin`.
     ///
-    /// If the session is expired, it cannot be used for signing operations, and a new session
-    /// must be created with a fresh identity token.
-    pub fn is_expired(&self) -> bool {
+    /// The identity token is only needed to obtain the certificate, so signing with the key
+    /// materials remains possible after the token expires.
+    fn expires_within(&self, margin: Duration) -> bool {
         let not_after = self
             .certs
             .cert
@@ -120,85 +168,548 @@ impl<'ctx> SigningSession<'ctx> {
             .not_after
             .to_system_time();
 
-        !self.identity_token.in_validity_period() || SystemTime::now() > not_after
+        SystemTime::now() + margin > not_after
+    }
+
+    /// Checks if the identity token or the certificate is expired.
+    fn is_expired(&self) -> bool {
+        !self.identity_token.in_validity_period() || self.expires_within(Duration::ZERO)
+    }
+}
+
+impl<'ctx> SigningSession<'ctx> {
+    /// How long before their expiry the identities of refreshing sessions are renewed.
+    const REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+    async fn new(
+        context: &'ctx SigningContext,
+        identity_token: IdentityToken,
+        token_source: Option<Box<dyn IdentityTokenSource>>,
+    ) -> SigstoreResult<SigningSession<'ctx>> {
+        let identity = SigningIdentity::new(context, identity_token).await?;
+
+        Ok(Self {
+            context,
+            token_source,
+            identity: StdMutex::new(Arc::new(identity)),
+            refresh: AsyncMutex::new(()),
+        })
+    }
+
+    async fn refreshing(
+        context: &'ctx SigningContext,
+        token_source: Box<dyn IdentityTokenSource>,
+    ) -> SigstoreResult<SigningSession<'ctx>> {
+        let identity_token = token_source.identity_token().await?;
+
+        Self::new(context, identity_token, Some(token_source)).await
+    }
+
+    /// Check if the session's identity token or key material is expired.
+    ///
+    /// If the session is expired, it cannot be used for signing operations, and a new session
+    /// must be created with a fresh identity token. Refreshing sessions renew their identity
+    /// instead, on their next signing operation, and only expire with their key material.
+    pub fn is_expired(&self) -> bool {
+        let identity = self.current_identity();
+        match self.token_source {
+            Some(_) => identity.expires_within(Duration::ZERO),
+            None => identity.is_expired(),
+        }
+    }
+
+    fn current_identity(&self) -> Arc<SigningIdentity> {
+        self.identity
+            .lock()
+            .expect("signing identity lock poisoned")
+            .clone()
     }
 
-    async fn sign_digest(&self, hasher: Sha256) -> SigstoreResult<SigningArtifact> {
-        if self.is_expired() {
-            return Err(SigstoreError::ExpiredSigningSession());
+    /// Returns the identity to sign with, renewing it from the token source if its certificate
+    /// is about to expire. A new identity token is only acquired to renew the certificate.
+    async fn identity(&self) -> SigstoreResult<Arc<SigningIdentity>> {
+        let identity = self.current_identity();
+        let Some(token_source) = &self.token_source else {
+            return match identity.is_expired() {
+                true => Err(SigstoreError::ExpiredSigningSession()),
+                false => Ok(identity),
+            };
+        };
+        if !identity.expires_within(Self::REFRESH_MARGIN) {
+            return Ok(identity);
+        }
+
+        let _refresh = self.refresh.lock().await;
+
+        // Another signing operation may have renewed the identity while we waited.
+        let identity = self.current_identity();
+        if !identity.expires_within(Self::REFRESH_MARGIN) {
+            return Ok(identity);
         }
 
-        // TODO(tnytown): verify SCT here, sigstore-rs#326
+        debug!("renewing the identity of the signing session");
+        let identity_token = token_source.identity_token().await?;
+        let identity = Arc::new(SigningIdentity::new(self.context, identity_token).await?);
+        *self
+            .identity
+            .lock()
+            .expect("signing identity lock poisoned") = identity.clone();
+
+        Ok(identity)
+    }
+
+    async fn sign_digest(
+        &self,
+        hasher: MessageHasher,
+        retry: &Retry,
+    ) -> SigstoreResult<SigningArtifact> {
+        let identity = self.identity().await?;
 
         // Sign artifact.
-        let input_hash: &[u8] = &hasher.clone().finalize();
-        let artifact_signature: p256::ecdsa::Signature = self.private_key.sign_digest(hasher);
-        let signature_bytes = artifact_signature.to_der().as_bytes().to_owned();
+        let input_digest = hasher.finalize();
+        let signature_bytes = identity.signer.sign_prehash(&input_digest.digest)?;
 
-        let cert = &self.certs.cert;
+        let cert = &identity.certs.cert;
 
         // Create the transparency log entry.
-        let proposed_entry = ProposedLogEntry::Hashedrekord {
-            api_version: "0.0.1".to_owned(),
-            spec: hashedrekord::Spec {
-                signature: hashedrekord::Signature {
-                    content: base64.encode(&signature_bytes),
-                    public_key: hashedrekord::PublicKey::new(
-                        base64.encode(cert.to_pem(pkcs8::LineEnding::LF)?),
-                    ),
-                },
-                data: hashedrekord::Data {
-                    hash: hashedrekord::Hash {
-                        algorithm: hashedrekord::AlgorithmKind::sha256,
-                        value: hex::encode(input_hash),
-                    },
-                },
+        let proposed_entry = hashedrekord_entry(
+            &input_digest,
+            &signature_bytes,
+            &cert.to_pem(pkcs8::LineEnding::LF)?,
+        );
+
+        let log_entry = retry
+            .run(|| self.context.log(proposed_entry.clone()))
+            .await?;
+        let timestamps = retry
+            .run(|| self.context.timestamp(&signature_bytes))
+            .await?;
+
+        Ok(SigningArtifact {
+            content: SignedContent::MessageSignature {
+                input_digest,
+                signature: signature_bytes,
             },
-        };
+            material: SigningMaterial::Certificate(cert.to_der()?),
+            log_entry,
+            timestamps,
+        })
+    }
 
-        let log_entry = create_log_entry(&self.context.rekor_config, proposed_entry)
-            .await
-            .map_err(|err| SigstoreError::RekorClientError(err.to_string()))?;
-        let log_entry = log_entry
-            .try_into()
-            .or(Err(SigstoreError::RekorClientError(
-                "Rekor returned malformed LogEntry".into(),
-            )))?;
+    /// Signs `payload` into a DSSE envelope of the given `payload_type` with the session's
+    /// identity, and records it in the transparency log as a `dsse` entry. If the identity is
+    /// expired, [`SigstoreError::ExpiredSigningSession`] is returned.
+    ///
+    /// To sign in-toto attestations, see [`SigningSession::sign_statement`].
+    pub async fn sign_dsse(
+        &self,
+        payload_type: &str,
+        payload: Vec<u8>,
+    ) -> SigstoreResult<SigningArtifact> {
+        let identity = self.identity().await?;
 
-        // TODO(tnytown): Maybe run through the verification flow here? See sigstore-rs#296.
+        // Sign the envelope's pre-authentication encoding.
+        let pae = intoto::pae(payload_type, &payload);
+        let signature_bytes = identity.signer.sign(&pae)?;
+
+        let cert = &identity.certs.cert;
+
+        let proposed_entry = dsse_entry(
+            payload_type,
+            &payload,
+            &signature_bytes,
+            &cert.to_pem(pkcs8::LineEnding::LF)?,
+        );
+
+        let log_entry = self.context.log(proposed_entry).await?;
+        let timestamps = self.context.timestamp(&signature_bytes).await?;
 
         Ok(SigningArtifact {
-            input_digest: input_hash.to_owned(),
-            cert: cert.to_der()?,
-            signature: signature_bytes,
+            content: SignedContent::dsse(payload_type, payload, signature_bytes),
+            material: SigningMaterial::Certificate(cert.to_der()?),
             log_entry,
+            timestamps,
         })
     }
 
+    /// Signs an in-toto [`Statement`] with the session's identity, producing a DSSE-enveloped
+    /// attestation. If the identity is expired, [`SigstoreError::ExpiredSigningSession`] is
+    /// returned.
+    pub async fn sign_statement(&self, statement: &Statement) -> SigstoreResult<SigningArtifact> {
+        let payload = serde_json::to_vec(statement)?;
+
+        self.sign_dsse(intoto::PAYLOAD_TYPE, payload).await
+    }
+
     /// Signs for the input with the session's identity. If the identity is expired,
     /// [`SigstoreError::ExpiredSigningSession`] is returned.
+    ///
+    /// The input is signed through its digest, computed with the hash function of the
+    /// context's [`SigningScheme`]. Ed25519 sessions cannot sign messages, only DSSE envelopes.
     pub async fn sign<R: AsyncRead + Unpin + Send + 'static>(
         &self,
         input: R,
     ) -> SigstoreResult<SigningArtifact> {
-        if self.is_expired() {
-            return Err(SigstoreError::ExpiredSigningSession());
+        let hasher =
+            MessageHasher::hash(self.context.signing_scheme, SyncIoBridge::new(input)).await?;
+
+        self.sign_digest(hasher, &Retry::NONE).await
+    }
+
+    /// Signs for each of the inputs with the session's identity, returning the result of each
+    /// signing operation in the order of the inputs.
+    ///
+    /// Inputs are hashed and uploaded to the transparency log concurrently, up to the
+    /// `max_concurrency` of the options. Uploads failing to reach the transparency log or
+    /// timestamp authority, or failing with a server error, are retried. A failure to sign one
+    /// of the inputs does not abort the others.
+    pub async fn sign_many<I, R>(
+        &self,
+        inputs: I,
+        options: &BatchSigningOptions,
+    ) -> Vec<SigstoreResult<SigningArtifact>>
+    where
+        I: IntoIterator<Item = R>,
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        let signing_scheme = self.context.signing_scheme;
+        let hashers = inputs.into_iter().map(|input| {
+            // The bridge must be created within the runtime that drives it.
+            async move { MessageHasher::hash(signing_scheme, SyncIoBridge::new(input)).await }
+        });
+
+        self.sign_hashed(hashers, options).await
+    }
+
+    /// Signs the digests computed by `hashers`, with the concurrency and retries of `options`.
+    async fn sign_hashed<I, F>(
+        &self,
+        hashers: I,
+        options: &BatchSigningOptions,
+    ) -> Vec<SigstoreResult<SigningArtifact>>
+    where
+        I: IntoIterator<Item = F>,
+        F: Future<Output = SigstoreResult<MessageHasher>>,
+    {
+        let retry = Retry {
+            max_retries: options.max_retries,
+            backoff: options.retry_backoff,
+        };
+
+        stream::iter(hashers)
+            .map(|hasher| async {
+                let hasher = hasher.await?;
+                self.sign_digest(hasher, &retry).await
+            })
+            .buffered(options.max_concurrency.max(1))
+            .collect()
+            .await
+    }
+}
+
+/// Options controlling how [`SigningSession::sign_many`] signs a batch of inputs.
+#[derive(Clone, Debug)]
+pub struct BatchSigningOptions {
+    /// The maximum number of inputs hashed, signed and uploaded to the transparency log at once.
+    pub max_concurrency: usize,
+    /// The number of times an upload to the transparency log or timestamp authority failing
+    /// with a transient error is retried, before giving up on the input.
+    pub max_retries: u32,
+    /// The delay before the first retry of an upload, doubled on every further retry.
+    pub retry_backoff: Duration,
+}
+
+impl Default for BatchSigningOptions {
+    fn default() -> Self {
+        Self {
+            max_concurrency: 16,
+            max_retries: 3,
+            retry_backoff: Duration::from_millis(500),
         }
+    }
+}
 
-        let mut sync_input = SyncIoBridge::new(input);
-        let hasher = tokio::task::spawn_blocking(move || -> SigstoreResult<_> {
-            let mut hasher = Sha256::new();
-            io::copy(&mut sync_input, &mut hasher)?;
-            Ok(hasher)
+/// How failed uploads of a signing session are retried.
+struct Retry {
+    max_retries: u32,
+    backoff: Duration,
+}
+
+impl Retry {
+    /// Uploads are attempted once.
+    const NONE: Self = Self {
+        max_retries: 0,
+        backoff: Duration::ZERO,
+    };
+
+    /// Runs `upload` until it succeeds, fails with an error that is not transient, or fails
+    /// more than `max_retries` times.
+    async fn run<T, F, Fut>(&self, mut upload: F) -> SigstoreResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = SigstoreResult<T>>,
+    {
+        let mut backoff = self.backoff;
+        for _ in 0..self.max_retries {
+            match upload().await {
+                Err(err) if Self::is_transient(&err) => {
+                    debug!("upload failed, retrying in {backoff:?}: {err}");
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                result => return result,
+            }
+        }
+
+        upload().await
+    }
+
+    /// Returns whether `err` may not occur again on retry: a failure to reach the service, or
+    /// a server error.
+    fn is_transient(err: &SigstoreError) -> bool {
+        match err {
+            SigstoreError::RekorError(err) => match err.as_ref() {
+                RekorError::Request(_) => true,
+                err => err.status().is_some_and(|status| status.is_server_error()),
+            },
+            SigstoreError::ReqwestError(err) => match err.status() {
+                Some(status) => status.is_server_error(),
+                None => true,
+            },
+            _ => false,
+        }
+    }
+}
+
+/// Returns the UUID of the existing entry a Rekor upload conflicted with, which Rekor names at
+/// the end of its error message.
+fn existing_entry_uuid(err: &RekorError) -> Option<&str> {
+    let RekorError::Response {
+        status: StatusCode::CONFLICT,
+        message,
+    } = err
+    else {
+        return None;
+    };
+
+    message
+        .rsplit(' ')
+        .next()
+        .filter(|uuid| !uuid.is_empty() && uuid.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// Returns whether `body`, the body of an entry served by Rekor, records `proposed_entry`.
+fn records_entry(body: &Body, proposed_entry: &ProposedLogEntry) -> bool {
+    match (body, proposed_entry) {
+        (Body::hashedrekord(body), ProposedLogEntry::Hashedrekord { spec, .. }) => {
+            serde_json::from_value::<hashedrekord::Spec>(body.spec.clone())
+                .is_ok_and(|recorded| recorded == *spec)
+        }
+        // Rekor records the digest of the envelope's payload and its signatures, along with the
+        // verifier of each.
+        (Body::dsse(body), ProposedLogEntry::Dsse { spec, .. }) => {
+            let Some(content) = &spec.proposed_content else {
+                return false;
+            };
+            let Ok(envelope) = serde_json::from_str::<Envelope>(&content.envelope) else {
+                return false;
+            };
+            let payload_hash = hashedrekord::Hash::new(
+                hashedrekord::AlgorithmKind::sha256,
+                hex::encode(Sha256::digest(&envelope.payload)),
+            );
+            let signatures = envelope
+                .signatures
+                .iter()
+                .zip(&content.verifiers)
+                .map(|(signature, verifier)| {
+                    dsse::Signature::new(base64.encode(&signature.sig), verifier.clone())
+                })
+                .collect::<Vec<_>>();
+
+            body.spec.payload_hash.as_ref() == Some(&payload_hash)
+                && body.spec.signatures == signatures
+        }
+        _ => false,
+    }
+}
+
+/// Checks that using a single one of the `valid` services of a signing configuration satisfies
+/// its `selector`. No service is used when none is valid.
+fn check_selector(service: &str, selector: ServiceSelector, valid: usize) -> SigstoreResult<()> {
+    match selector {
+        ServiceSelector::Any => Ok(()),
+        ServiceSelector::All if valid <= 1 => Ok(()),
+        ServiceSelector::Exact(count) if count as usize == valid.min(1) => Ok(()),
+        selector => Err(SigstoreError::SigningConfigError(format!(
+            "{service} selector {selector:?} is unsupported with {valid} valid services: at most one is used"
+        ))),
+    }
+}
+
+/// Builds a `hashedrekord` transparency log entry for `signature` over `input_digest`,
+/// verifiable with `verifier`, a PEM-encoded certificate or public key.
+fn hashedrekord_entry(
+    input_digest: &MessageDigest,
+    signature: &[u8],
+    verifier: &str,
+) -> ProposedLogEntry {
+    ProposedLogEntry::Hashedrekord {
+        api_version: "0.0.1".to_owned(),
+        spec: hashedrekord::Spec {
+            signature: hashedrekord::Signature {
+                content: base64.encode(signature),
+                public_key: hashedrekord::PublicKey::new(base64.encode(verifier)),
+            },
+            data: hashedrekord::Data {
+                hash: hashedrekord::Hash {
+                    algorithm: input_digest.algorithm.rekor_algorithm(),
+                    value: hex::encode(&input_digest.digest),
+                },
+            },
+        },
+    }
+}
+
+/// Builds a `dsse` transparency log entry for a DSSE envelope holding `signature` over
+/// `payload`, verifiable with `verifier`, a PEM-encoded certificate or public key.
+fn dsse_entry(
+    payload_type: &str,
+    payload: &[u8],
+    signature: &[u8],
+    verifier: &str,
+) -> ProposedLogEntry {
+    // Rekor expects the envelope in its JSON serialization, and derives the entry's payload
+    // hash and signatures from it.
+    let envelope = json!({
+        "payloadType": payload_type,
+        "payload": base64.encode(payload),
+        "signatures": [{ "sig": base64.encode(signature) }],
+    });
+    ProposedLogEntry::Dsse {
+        api_version: "0.0.1".to_owned(),
+        spec: dsse::Spec::new(dsse::ProposedContent::new(
+            envelope.to_string(),
+            vec![base64.encode(verifier)],
+        )),
+    }
+}
+
+/// An asynchronous Sigstore signing session backed by an existing key.
+///
+/// Unlike [`SigningSession`], key signing sessions do not request a certificate from Fulcio:
+/// entries are uploaded to the transparency log with the signer's public key, and bundles carry
+/// a hint of the key instead of a certificate. Verifiers must be given the public key out of
+/// band, see the `PublicKey` verification policy.
+///
+/// To construct a synchronous [`blocking::KeySigningSession`], use
+/// [`SigningContext::blocking_key_signer()`].
+pub struct KeySigningSession<'ctx> {
+    context: &'ctx SigningContext,
+    signer: SigStoreSigner,
+    public_key: String,
+    hint: String,
+}
+
+impl<'ctx> KeySigningSession<'ctx> {
+    fn new(
+        context: &'ctx SigningContext,
+        signer: SigStoreSigner,
+    ) -> SigstoreResult<KeySigningSession<'ctx>> {
+        let key_pair = signer.to_sigstore_keypair()?;
+        let public_key = key_pair.public_key_to_pem()?;
+        let hint = hex::encode(key_id(&key_pair.public_key_to_der()?));
+
+        Ok(Self {
+            context,
+            signer,
+            public_key,
+            hint,
+        })
+    }
+
+    /// Returns the hint identifying the session's key in bundles: the hex-encoded SHA-256
+    /// digest of its DER-encoded `SubjectPublicKeyInfo`.
+    pub fn hint(&self) -> &str {
+        &self.hint
+    }
+
+    async fn sign_digest(&self, hasher: MessageHasher) -> SigstoreResult<SigningArtifact> {
+        let input_digest = hasher.finalize();
+        let signature_bytes = self.signer.sign_prehash(&input_digest.digest)?;
+
+        let proposed_entry = hashedrekord_entry(&input_digest, &signature_bytes, &self.public_key);
+
+        let log_entry = self.context.log(proposed_entry).await?;
+        let timestamps = self.context.timestamp(&signature_bytes).await?;
+
+        Ok(SigningArtifact {
+            content: SignedContent::MessageSignature {
+                input_digest,
+                signature: signature_bytes,
+            },
+            material: SigningMaterial::PublicKey {
+                hint: self.hint.clone(),
+            },
+            log_entry,
+            timestamps,
+        })
+    }
+
+    /// Signs `payload` into a DSSE envelope of the given `payload_type` with the session's key,
+    /// and records it in the transparency log as a `dsse` entry.
+    ///
+    /// To sign in-toto attestations, see [`KeySigningSession::sign_statement`].
+    pub async fn sign_dsse(
+        &self,
+        payload_type: &str,
+        payload: Vec<u8>,
+    ) -> SigstoreResult<SigningArtifact> {
+        let pae = intoto::pae(payload_type, &payload);
+        let signature_bytes = self.signer.sign(&pae)?;
+
+        let proposed_entry = dsse_entry(payload_type, &payload, &signature_bytes, &self.public_key);
+
+        let log_entry = self.context.log(proposed_entry).await?;
+        let timestamps = self.context.timestamp(&signature_bytes).await?;
+
+        Ok(SigningArtifact {
+            content: SignedContent::dsse(payload_type, payload, signature_bytes),
+            material: SigningMaterial::PublicKey {
+                hint: self.hint.clone(),
+            },
+            log_entry,
+            timestamps,
         })
-        .await??;
+    }
+
+    /// Signs an in-toto [`Statement`] with the session's key, producing a DSSE-enveloped
+    /// attestation.
+    pub async fn sign_statement(&self, statement: &Statement) -> SigstoreResult<SigningArtifact> {
+        let payload = serde_json::to_vec(statement)?;
+
+        self.sign_dsse(intoto::PAYLOAD_TYPE, payload).await
+    }
+
+    /// Signs for the input with the session's key.
+    ///
+    /// The input is signed through its digest, computed with the hash function of the key's
+    /// [`SigningScheme`]. Ed25519 keys cannot sign messages, only DSSE envelopes.
+    pub async fn sign<R: AsyncRead + Unpin + Send + 'static>(
+        &self,
+        input: R,
+    ) -> SigstoreResult<SigningArtifact> {
+        let hasher =
+            MessageHasher::hash(self.signer.signing_scheme(), SyncIoBridge::new(input)).await?;
 
         self.sign_digest(hasher).await
     }
 }
 
 pub mod blocking {
-    use super::{SigningSession as AsyncSigningSession, *};
+    use super::{
+        KeySigningSession as AsyncKeySigningSession, SigningSession as AsyncSigningSession, *,
+    };
 
     /// A synchronous Sigstore signing session.
     ///
@@ -217,14 +728,26 @@ pub mod blocking {
             let rt = tokio::runtime::Builder::new_current_thread()
                 .enable_all()
                 .build()?;
-            let inner = rt.block_on(AsyncSigningSession::new(ctx, token))?;
+            let inner = rt.block_on(AsyncSigningSession::new(ctx, token, None))?;
+            Ok(Self { inner, rt })
+        }
+
+        pub(crate) fn refreshing(
+            ctx: &'ctx SigningContext,
+            token_source: Box<dyn IdentityTokenSource>,
+        ) -> SigstoreResult<Self> {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?;
+            let inner = rt.block_on(AsyncSigningSession::refreshing(ctx, token_source))?;
             Ok(Self { inner, rt })
         }
 
         /// Check if the session's identity token or key material is expired.
         ///
         /// If the session is expired, it cannot be used for signing operations, and a new session
-        /// must be created with a fresh identity token.
+        /// must be created with a fresh identity token. Refreshing sessions renew their identity
+        /// instead, on their next signing operation.
         pub fn is_expired(&self) -> bool {
             self.inner.is_expired()
         }
@@ -232,13 +755,117 @@ pub mod blocking {
         /// Signs for the input with the session's identity. If the identity is expired,
         /// [`SigstoreError::ExpiredSigningSession`] is returned.
         pub fn sign<R: Read>(&self, mut input: R) -> SigstoreResult<SigningArtifact> {
-            let mut hasher = Sha256::new();
+            let mut hasher = MessageHasher::new(&self.inner.context.signing_scheme)?;
+            io::copy(&mut input, &mut hasher)?;
+            self.rt
+                .block_on(self.inner.sign_digest(hasher, &Retry::NONE))
+        }
+
+        /// Signs for each of the inputs with the session's identity, returning the result of
+        /// each signing operation in the order of the inputs. For more information, see
+        /// [`SigningSession::sign_many`](super::SigningSession::sign_many).
+        pub fn sign_many<I, R>(
+            &self,
+            inputs: I,
+            options: &BatchSigningOptions,
+        ) -> Vec<SigstoreResult<SigningArtifact>>
+        where
+            I: IntoIterator<Item = R>,
+            R: Read + Send + 'static,
+        {
+            let signing_scheme = self.inner.context.signing_scheme;
+            let hashers = inputs
+                .into_iter()
+                .map(|input| MessageHasher::hash(signing_scheme, input));
+
+            self.rt.block_on(self.inner.sign_hashed(hashers, options))
+        }
+
+        /// Signs `payload` into a DSSE envelope of the given `payload_type` with the session's
+        /// identity. If the identity is expired, [`SigstoreError::ExpiredSigningSession`] is
+        /// returned.
+        pub fn sign_dsse(
+            &self,
+            payload_type: &str,
+            payload: Vec<u8>,
+        ) -> SigstoreResult<SigningArtifact> {
+            self.rt
+                .block_on(self.inner.sign_dsse(payload_type, payload))
+        }
+
+        /// Signs an in-toto [`Statement`] with the session's identity, producing a DSSE-enveloped
+        /// attestation. If the identity is expired, [`SigstoreError::ExpiredSigningSession`] is
+        /// returned.
+        pub fn sign_statement(&self, statement: &Statement) -> SigstoreResult<SigningArtifact> {
+            self.rt.block_on(self.inner.sign_statement(statement))
+        }
+    }
+
+    /// A synchronous Sigstore signing session backed by an existing key.
+    ///
+    /// For more information, see [`KeySigningSession`](super::KeySigningSession).
+    ///
+    /// This signing session operates synchronously, thus it cannot be used in an asynchronous context.
+    /// To construct an asynchronous [`KeySigningSession`](super::KeySigningSession), use
+    /// [`SigningContext::key_signer()`].
+    pub struct KeySigningSession<'ctx> {
+        inner: AsyncKeySigningSession<'ctx>,
+        rt: tokio::runtime::Runtime,
+    }
+
+    impl<'ctx> KeySigningSession<'ctx> {
+        pub(crate) fn new(
+            ctx: &'ctx SigningContext,
+            signer: SigStoreSigner,
+        ) -> SigstoreResult<Self> {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?;
+            let inner = AsyncKeySigningSession::new(ctx, signer)?;
+            Ok(Self { inner, rt })
+        }
+
+        /// Returns the hint identifying the session's key in bundles.
+        pub fn hint(&self) -> &str {
+            self.inner.hint()
+        }
+
+        /// Signs for the input with the session's key.
+        pub fn sign<R: Read>(&self, mut input: R) -> SigstoreResult<SigningArtifact> {
+            let mut hasher = MessageHasher::new(&self.inner.signer.signing_scheme())?;
             io::copy(&mut input, &mut hasher)?;
             self.rt.block_on(self.inner.sign_digest(hasher))
         }
+
+        /// Signs `payload` into a DSSE envelope of the given `payload_type` with the session's
+        /// key.
+        pub fn sign_dsse(
+            &self,
+            payload_type: &str,
+            payload: Vec<u8>,
+        ) -> SigstoreResult<SigningArtifact> {
+            self.rt
+                .block_on(self.inner.sign_dsse(payload_type, payload))
+        }
+
+        /// Signs an in-toto [`Statement`] with the session's key, producing a DSSE-enveloped
+        /// attestation.
+        pub fn sign_statement(&self, statement: &Statement) -> SigstoreResult<SigningArtifact> {
+            self.rt.block_on(self.inner.sign_statement(statement))
+        }
     }
 }
 
+/// How a [`SigningContext`] verifies the SCTs of Fulcio certificates.
+enum SctVerification {
+    /// No CT log keys are configured, and signing is refused.
+    Unconfigured,
+    /// SCTs are verified against the keys of the CT logs held in the keyring.
+    Keyring(Keyring),
+    /// SCTs are not verified.
+    Disabled,
+}
+
 /// A Sigstore signing context.
 ///
 /// Contexts hold Fulcio (CA) and Rekor (CT) configurations which signing sessions can be
@@ -246,33 +873,189 @@ pub mod blocking {
 /// the public-good Sigstore infrastructure.
 pub struct SigningContext {
     fulcio: FulcioClient,
-    rekor_config: RekorConfiguration,
+    rekor: RekorClient,
+    sct_verification: SctVerification,
+    timestamp_authority: Option<TimestampAuthorityClient>,
+    transparency_log: bool,
+    signing_scheme: SigningScheme,
 }
 
 impl SigningContext {
     /// Manually constructs a [`SigningContext`] from its constituent data.
+    ///
+    /// The SCTs of Fulcio certificates must be verified: signing sessions cannot be created until
+    /// the context is configured with [`SigningContext::with_ctfe_keyring`], or explicitly opts
+    /// out with [`SigningContext::without_sct_verification`].
     pub fn new(fulcio: FulcioClient, rekor_config: RekorConfiguration) -> Self {
         Self {
             fulcio,
-            rekor_config,
+            rekor: RekorClient::new(rekor_config),
+            sct_verification: SctVerification::Unconfigured,
+            timestamp_authority: None,
+            transparency_log: true,
+            signing_scheme: SigningScheme::ECDSA_P256_SHA256_ASN1,
+        }
+    }
+
+    /// Configures the context to verify the SCTs of Fulcio certificates against the keys of the
+    /// CT logs held in `ctfe_keyring`.
+    pub fn with_ctfe_keyring(mut self, ctfe_keyring: Keyring) -> Self {
+        self.sct_verification = SctVerification::Keyring(ctfe_keyring);
+        self
+    }
+
+    /// Configures the context not to verify the SCTs of Fulcio certificates.
+    ///
+    /// Without SCT verification, certificates that were never logged to a CT log are accepted.
+    pub fn without_sct_verification(mut self) -> Self {
+        self.sct_verification = SctVerification::Disabled;
+        self
+    }
+
+    /// Configures the context to generate the ephemeral keys of its signing sessions with the
+    /// given [`SigningScheme`], instead of ECDSA P-256 with SHA-256.
+    ///
+    /// Messages are signed through their digest with the scheme's hash function. Ed25519 keys
+    /// cannot sign message digests, thus their sessions may only sign DSSE envelopes.
+    pub fn with_signing_scheme(mut self, signing_scheme: SigningScheme) -> Self {
+        self.signing_scheme = signing_scheme;
+        self
+    }
+
+    /// Configures the context to request an RFC 3161 signed timestamp over each signature from
+    /// the given timestamp authority, and to embed it in the resulting bundles.
+    pub fn with_timestamp_authority(
+        mut self,
+        timestamp_authority: TimestampAuthorityClient,
+    ) -> Self {
+        self.timestamp_authority = Some(timestamp_authority);
+        self
+    }
+
+    /// Configures the context not to record signatures in the transparency log.
+    ///
+    /// The resulting bundles carry no log entry: their only evidence of the time of signing is
+    /// the signed timestamps of the context's timestamp authority, which becomes mandatory (see
+    /// [`SigningContext::with_timestamp_authority`]). Verifiers must be configured to accept
+    /// such bundles.
+    pub fn without_transparency_log(mut self) -> Self {
+        self.transparency_log = false;
+        self
+    }
+
+    /// Constructs a [`SigningContext`] against the services of a [`SigningConfig`], trusting the
+    /// CT logs of `trust_root`.
+    ///
+    /// The first currently valid CA, OIDC provider and transparency log of each kind are used.
+    /// If the configuration lists a timestamp authority, signatures are timestamped by it.
+    /// Contexts use a single transparency log and timestamp authority: configurations whose
+    /// selectors require several of either are rejected with
+    /// [`SigstoreError::SigningConfigError`].
+    pub fn from_signing_config<R: TrustRoot + ?Sized>(
+        signing_config: &SigningConfig,
+        trust_root: &R,
+    ) -> SigstoreResult<Self> {
+        let missing = |service| SigstoreError::SigningConfigError(format!("no valid {service}"));
+        check_selector(
+            "transparency log",
+            signing_config.tlog_selector,
+            signing_config.tlogs().count(),
+        )?;
+        check_selector(
+            "timestamp authority",
+            signing_config.tsa_selector,
+            signing_config.tsas().count(),
+        )?;
+
+        // Fulcio endpoints are resolved relative to the CA URL.
+        let mut ca_url = signing_config
+            .ca()
+            .ok_or_else(|| missing("CA"))?
+            .url
+            .clone();
+        if !ca_url.path().ends_with('/') {
+            ca_url.set_path(&format!("{}/", ca_url.path()));
         }
+        let oidc_url = &signing_config
+            .oidc()
+            .ok_or_else(|| missing("OIDC provider"))?
+            .url;
+        let token_provider =
+            OauthTokenProvider::default().with_issuer(oidc_url.as_str().trim_end_matches('/'));
+        let tlog_url = &signing_config
+            .tlog()
+            .ok_or_else(|| missing("transparency log"))?
+            .url;
+        let rekor_config = RekorConfiguration {
+            base_path: tlog_url.as_str().trim_end_matches('/').to_owned(),
+            ..Default::default()
+        };
+
+        let context = Self::new(
+            FulcioClient::new(ca_url, crate::fulcio::TokenProvider::Oauth(token_provider)),
+            rekor_config,
+        )
+        .with_ctfe_keyring(Keyring::from_log_keys(trust_root.ctfe_log_keys()?)?);
+
+        Ok(match signing_config.tsa() {
+            Some(tsa) => {
+                context.with_timestamp_authority(TimestampAuthorityClient::new(tsa.url.clone()))
+            }
+            None => context,
+        })
     }
 
     /// Returns a [`SigningContext`] configured against the public-good production Sigstore
     /// infrastructure.
+    ///
+    /// The SCTs of Fulcio certificates are verified against the CT logs of the public-good trust
+    /// root embedded in this release. Use [`SigningContext::async_production`] to fetch the
+    /// current trust root instead. Without the `sigstore-trust-root` feature, no CT log keys are
+    /// known, and the context must be configured with [`SigningContext::with_ctfe_keyring`].
     pub fn production() -> SigstoreResult<Self> {
-        Ok(Self::new(
+        let context = Self::new(
             FulcioClient::new(
                 Url::parse(FULCIO_ROOT).expect("constant FULCIO root fails to parse!"),
                 crate::fulcio::TokenProvider::Oauth(OauthTokenProvider::default()),
             ),
             Default::default(),
-        ))
+        );
+
+        #[cfg(feature = "sigstore-trust-root")]
+        let context = context.with_ctfe_keyring(Keyring::from_log_keys(
+            SigstoreTrustRoot::embedded()?.ctfe_log_keys()?,
+        )?);
+
+        Ok(context)
+    }
+
+    /// Returns a [`SigningContext`] configured against the public-good production Sigstore
+    /// infrastructure, verifying the SCTs of Fulcio certificates against the CT logs of the
+    /// public-good trust root.
+    #[cfg(feature = "sigstore-trust-root")]
+    pub async fn async_production() -> SigstoreResult<Self> {
+        let trust_root = SigstoreTrustRoot::new(None).await?;
+
+        Ok(Self::production()?
+            .with_ctfe_keyring(Keyring::from_log_keys(trust_root.ctfe_log_keys()?)?))
     }
 
     /// Configures and returns a [`SigningSession`] with the held context.
     pub async fn signer(&self, identity_token: IdentityToken) -> SigstoreResult<SigningSession> {
-        SigningSession::new(self, identity_token).await
+        SigningSession::new(self, identity_token, None).await
+    }
+
+    /// Configures and returns a [`SigningSession`] with the held context, acquiring its identity
+    /// tokens from `token_source`.
+    ///
+    /// The session transparently acquires a new identity token and Fulcio certificate when its
+    /// current certificate is about to expire, and thus never returns
+    /// [`SigstoreError::ExpiredSigningSession`].
+    pub async fn refreshing_signer<S: IdentityTokenSource + 'static>(
+        &self,
+        token_source: S,
+    ) -> SigstoreResult<SigningSession<'_>> {
+        SigningSession::refreshing(self, Box::new(token_source)).await
     }
 
     /// Configures and returns a [`blocking::SigningSession`] with the held context.
@@ -284,50 +1067,839 @@ impl SigningContext {
     ) -> SigstoreResult<blocking::SigningSession> {
         blocking::SigningSession::new(self, identity_token)
     }
+
+    /// Configures and returns a [`blocking::SigningSession`] with the held context, acquiring
+    /// its identity tokens from `token_source`. For more information, see
+    /// [`SigningContext::refreshing_signer`].
+    ///
+    /// Async contexts must use [`SigningContext::refreshing_signer`].
+    pub fn blocking_refreshing_signer<S: IdentityTokenSource + 'static>(
+        &self,
+        token_source: S,
+    ) -> SigstoreResult<blocking::SigningSession<'_>> {
+        blocking::SigningSession::refreshing(self, Box::new(token_source))
+    }
+
+    /// Configures and returns a [`KeySigningSession`] signing with `signer`, with the held
+    /// context. No certificate is requested from Fulcio.
+    pub fn key_signer(&self, signer: SigStoreSigner) -> SigstoreResult<KeySigningSession<'_>> {
+        KeySigningSession::new(self, signer)
+    }
+
+    /// Configures and returns a [`blocking::KeySigningSession`] signing with `signer`, with the
+    /// held context.
+    ///
+    /// Async contexts must use [`SigningContext::key_signer`].
+    pub fn blocking_key_signer(
+        &self,
+        signer: SigStoreSigner,
+    ) -> SigstoreResult<blocking::KeySigningSession<'_>> {
+        blocking::KeySigningSession::new(self, signer)
+    }
+
+    /// Uploads `proposed_entry` to the transparency log, returning the resulting entry in bundle
+    /// format. Returns `None` if the context does not record signatures in the log.
+    async fn log(
+        &self,
+        proposed_entry: ProposedLogEntry,
+    ) -> SigstoreResult<Option<TransparencyLogEntry>> {
+        if !self.transparency_log {
+            return Ok(None);
+        }
+
+        let log_entry = match self.rekor.upload(proposed_entry.clone()).await {
+            Ok(log_entry) => log_entry,
+            Err(err) => match existing_entry_uuid(&err) {
+                // The entry was recorded by an earlier upload whose response was lost.
+                Some(uuid) => {
+                    let log_entry = self.rekor.entry_by_uuid(uuid).await.map_err(Box::new)?;
+                    if !records_entry(&log_entry.body, &proposed_entry) {
+                        return Err(SigstoreError::RekorClientError(format!(
+                            "conflicting Rekor log entry {uuid} does not record the signature"
+                        )));
+                    }
+                    log_entry
+                }
+                None => return Err(Box::new(err))?,
+            },
+        };
+
+        // TODO(tnytown): Maybe run through the verification flow here? See sigstore-rs#296.
+
+        log_entry
+            .try_into()
+            .map(Some)
+            .or(Err(SigstoreError::RekorClientError(
+                "Rekor returned malformed LogEntry".into(),
+            )))
+    }
+
+    /// Requests signed timestamps over `signature` from the timestamp authority, if any.
+    async fn timestamp(&self, signature: &[u8]) -> SigstoreResult<Vec<Vec<u8>>> {
+        match &self.timestamp_authority {
+            Some(tsa) => Ok(vec![tsa.timestamp(signature).await?]),
+            None if !self.transparency_log => Err(SigstoreError::TimestampAuthorityClientError(
+                "signatures must be timestamped when they are not recorded in the transparency log"
+                    .into(),
+            )),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+/// A client for an RFC 3161 timestamp authority (TSA).
+pub struct TimestampAuthorityClient {
+    url: Url,
+    client: reqwest::Client,
+}
+
+impl TimestampAuthorityClient {
+    /// Creates a client for the timestamp authority accepting `TimeStampReq`s at `url`, e.g.
+    /// `https://timestamp.sigstore.dev/api/v1/timestamp`.
+    pub fn new(url: Url) -> Self {
+        Self {
+            url,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Requests a signed timestamp over `signature`, returning the DER-encoded `TimeStampResp`.
+    ///
+    /// The response is checked to grant a timestamp over `signature`, but its signer is not
+    /// verified.
+    pub async fn timestamp(&self, signature: &[u8]) -> SigstoreResult<Vec<u8>> {
+        let request = timestamp_request(signature)
+            .map_err(|err| SigstoreError::TimestampAuthorityClientError(err.to_string()))?;
+
+        let response = self
+            .client
+            .post(self.url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/timestamp-query")
+            .header(reqwest::header::ACCEPT, "application/timestamp-reply")
+            .body(request)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?
+            .to_vec();
+
+        check_timestamp_response(&response, signature)
+            .map_err(|err| SigstoreError::TimestampAuthorityClientError(err.to_string()))?;
+
+        Ok(response)
+    }
+}
+
+impl MessageHasher {
+    /// Creates a hasher using the hash function of `signing_scheme`.
+    fn new(signing_scheme: &SigningScheme) -> SigstoreResult<Self> {
+        let algorithm = match signing_scheme {
+            SigningScheme::ECDSA_P256_SHA256_ASN1
+            | SigningScheme::RSA_PSS_SHA256(_)
+            | SigningScheme::RSA_PKCS1_SHA256(_) => DigestAlgorithm::Sha256,
+            SigningScheme::ECDSA_P384_SHA384_ASN1
+            | SigningScheme::RSA_PSS_SHA384(_)
+            | SigningScheme::RSA_PKCS1_SHA384(_) => DigestAlgorithm::Sha384,
+            SigningScheme::RSA_PSS_SHA512(_) | SigningScheme::RSA_PKCS1_SHA512(_) => {
+                DigestAlgorithm::Sha512
+            }
+            SigningScheme::ED25519 => {
+                return Err(SigstoreError::PrehashSigningUnsupported(
+                    signing_scheme.to_string(),
+                ))
+            }
+        };
+
+        Ok(Self::from_algorithm(algorithm))
+    }
+
+    /// Hashes `input` on a blocking thread.
+    async fn hash<R: Read + Send + 'static>(
+        signing_scheme: SigningScheme,
+        mut input: R,
+    ) -> SigstoreResult<Self> {
+        let mut hasher = Self::new(&signing_scheme)?;
+        tokio::task::spawn_blocking(move || -> SigstoreResult<_> {
+            io::copy(&mut input, &mut hasher)?;
+            Ok(hasher)
+        })
+        .await?
+    }
+}
+
+/// Builds a certificate signing request for `subject` and the key of `signer`, signed by
+/// it to prove its possession.
+fn cert_request(signer: &SigStoreSigner, subject: Name) -> SigstoreResult<CertReq> {
+    let public_key =
+        SubjectPublicKeyInfoOwned::from_der(&signer.to_sigstore_keypair()?.public_key_to_der()?)?;
+    let mut info = CertReqInfo {
+        version: CertReqVersion::V1,
+        subject,
+        public_key,
+        attributes: Default::default(),
+    };
+    let basic_constraints = x509_ext::BasicConstraints {
+        ca: false,
+        path_len_constraint: None,
+    }
+    .to_extension(&info.subject, &[])?;
+    info.attributes
+        .insert(ExtensionReq(vec![basic_constraints]).try_into()?)?;
+
+    let signature = signer.sign(&info.to_der()?)?;
+
+    Ok(CertReq {
+        info,
+        algorithm: signature_algorithm(&signer.signing_scheme())?,
+        signature: BitString::from_bytes(&signature)?,
+    })
+}
+
+/// Returns the identifier of the signature algorithm of `signing_scheme`, for certificate
+/// signing requests.
+fn signature_algorithm(signing_scheme: &SigningScheme) -> SigstoreResult<AlgorithmIdentifierOwned> {
+    let algorithm = |oid, parameters| AlgorithmIdentifierOwned { oid, parameters };
+    let pss = |result: spki::Result<AlgorithmIdentifierOwned>| {
+        result.map_err(|e| SigstoreError::PKCS8SpkiError(e.to_string()))
+    };
+
+    Ok(match signing_scheme {
+        SigningScheme::ECDSA_P256_SHA256_ASN1 => algorithm(rfc5912::ECDSA_WITH_SHA_256, None),
+        SigningScheme::ECDSA_P384_SHA384_ASN1 => algorithm(rfc5912::ECDSA_WITH_SHA_384, None),
+        SigningScheme::ED25519 => algorithm(rfc8410::ID_ED_25519, None),
+        SigningScheme::RSA_PKCS1_SHA256(_) => {
+            algorithm(rfc5912::SHA_256_WITH_RSA_ENCRYPTION, Some(Null.into()))
+        }
+        SigningScheme::RSA_PKCS1_SHA384(_) => {
+            algorithm(rfc5912::SHA_384_WITH_RSA_ENCRYPTION, Some(Null.into()))
+        }
+        SigningScheme::RSA_PKCS1_SHA512(_) => {
+            algorithm(rfc5912::SHA_512_WITH_RSA_ENCRYPTION, Some(Null.into()))
+        }
+        SigningScheme::RSA_PSS_SHA256(_) => pss(get_default_pss_signature_algo_id::<Sha256>())?,
+        SigningScheme::RSA_PSS_SHA384(_) => pss(get_default_pss_signature_algo_id::<Sha384>())?,
+        SigningScheme::RSA_PSS_SHA512(_) => pss(get_default_pss_signature_algo_id::<Sha512>())?,
+    })
+}
+
+/// The signed content of a [`SigningArtifact`].
+enum SignedContent {
+    MessageSignature {
+        input_digest: MessageDigest,
+        signature: Vec<u8>,
+    },
+    DsseEnvelope(Envelope),
+}
+
+impl SignedContent {
+    fn dsse(payload_type: &str, payload: Vec<u8>, signature: Vec<u8>) -> Self {
+        Self::DsseEnvelope(Envelope {
+            payload,
+            payload_type: payload_type.to_owned(),
+            signatures: vec![DsseSignature {
+                sig: signature,
+                keyid: String::new(),
+            }],
+        })
+    }
+}
+
+/// The material to verify a [`SigningArtifact`]'s signature with.
+enum SigningMaterial {
+    /// A DER-encoded signing certificate.
+    Certificate(Vec<u8>),
+    /// A hint identifying a public key distributed out of band.
+    PublicKey { hint: String },
 }
 
 /// A signature and its associated metadata.
 pub struct SigningArtifact {
-    input_digest: Vec<u8>,
-    cert: Vec<u8>,
-    signature: Vec<u8>,
-    log_entry: TransparencyLogEntry,
+    content: SignedContent,
+    material: SigningMaterial,
+    log_entry: Option<TransparencyLogEntry>,
+    timestamps: Vec<Vec<u8>>,
 }
 
 impl SigningArtifact {
-    /// Consumes the signing artifact and produces a Sigstore [`Bundle`].
+    /// Consumes the signing artifact and produces a Sigstore [`Bundle`], in the latest bundle
+    /// format ([`Version::Bundle0_3`]).
     ///
     /// The resulting bundle can be serialized with [`serde_json`].
     pub fn to_bundle(self) -> Bundle {
-        // NOTE: We explicitly only include the leaf certificate in the bundle's "chain"
-        // here: the specs explicitly forbid the inclusion of the root certificate,
-        // and discourage inclusion of any intermediates (since they're in the root of
-        // trust already).
-        let x509_certificate_chain = X509CertificateChain {
-            certificates: vec![X509Certificate {
-                raw_bytes: self.cert,
-            }],
+        self.to_versioned_bundle(Version::Bundle0_3)
+    }
+
+    /// Consumes the signing artifact and produces a Sigstore [`Bundle`] in the given format.
+    ///
+    /// Older formats should only be used for compatibility with verifiers that do not support
+    /// the latest one.
+    pub fn to_versioned_bundle(self, version: Version) -> Bundle {
+        let content = match self.material {
+            SigningMaterial::Certificate(raw_bytes) => {
+                let certificate = X509Certificate { raw_bytes };
+                match version {
+                    // NOTE: We explicitly only include the leaf certificate in the bundle's "chain"
+                    // here: the specs explicitly forbid the inclusion of the root certificate,
+                    // and discourage inclusion of any intermediates (since they're in the root of
+                    // trust already).
+                    Version::Bundle0_1 | Version::Bundle0_2 => {
+                        verification_material::Content::X509CertificateChain(X509CertificateChain {
+                            certificates: vec![certificate],
+                        })
+                    }
+                    Version::Bundle0_3 => verification_material::Content::Certificate(certificate),
+                }
+            }
+            SigningMaterial::PublicKey { hint } => {
+                verification_material::Content::PublicKey(PublicKeyIdentifier { hint })
+            }
         };
 
+        let timestamp_verification_data =
+            (!self.timestamps.is_empty()).then(|| TimestampVerificationData {
+                rfc3161_timestamps: self
+                    .timestamps
+                    .into_iter()
+                    .map(|signed_timestamp| Rfc3161SignedTimestamp { signed_timestamp })
+                    .collect(),
+            });
+
         let verification_material = Some(VerificationMaterial {
-            timestamp_verification_data: None,
-            tlog_entries: vec![self.log_entry],
-            content: Some(verification_material::Content::X509CertificateChain(
-                x509_certificate_chain,
-            )),
+            timestamp_verification_data,
+            tlog_entries: self.log_entry.into_iter().collect(),
+            content: Some(content),
         });
 
-        let message_signature = MessageSignature {
-            message_digest: Some(HashOutput {
-                algorithm: HashAlgorithm::Sha2256.into(),
-                digest: self.input_digest,
+        let content = match self.content {
+            SignedContent::MessageSignature {
+                input_digest,
+                signature,
+            } => bundle::Content::MessageSignature(MessageSignature {
+                message_digest: Some(HashOutput {
+                    algorithm: input_digest.algorithm.bundle_algorithm().into(),
+                    digest: input_digest.digest,
+                }),
+                signature,
             }),
-            signature: self.signature,
+            SignedContent::DsseEnvelope(envelope) => bundle::Content::DsseEnvelope(envelope),
         };
         Bundle {
-            media_type: Version::Bundle0_2.to_string(),
+            media_type: version.to_string(),
             verification_material,
-            content: Some(bundle::Content::MessageSignature(message_signature)),
+            content: Some(content),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rstest::rstest;
+    use sha2::Digest;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use async_trait::async_trait;
+    use openssl::pkey::PKey;
+    use pkcs8::der::DecodePem;
+    use serde_json::Value;
+
+    use super::*;
+    use crate::crypto::tests::{generate_certificate, CertGenerationOptions};
+    use crate::crypto::{CosignVerificationKey, Signature};
+    use crate::fulcio::TokenProvider;
+    use crate::rekor::client::tests::{serve, FakeRekor, Upload};
+    use crate::trust::{signing_config::Service, ManualTrustRoot};
+
+    #[rstest]
+    #[case(SigningScheme::ECDSA_P256_SHA256_ASN1, rfc5912::ECDSA_WITH_SHA_256)]
+    #[case(SigningScheme::ECDSA_P384_SHA384_ASN1, rfc5912::ECDSA_WITH_SHA_384)]
+    #[case(SigningScheme::ED25519, rfc8410::ID_ED_25519)]
+    fn cert_request_signed_by_key(
+        #[case] signing_scheme: SigningScheme,
+        #[case] algorithm: const_oid::ObjectIdentifier,
+    ) {
+        let signer = signing_scheme
+            .create_signer()
+            .expect("failed to create signer");
+        let subject = Name::from_str("CN=sigstore").unwrap();
+        let cert_req = cert_request(&signer, subject).expect("failed to build request");
+
+        assert_eq!(cert_req.algorithm.oid, algorithm);
+        assert_eq!(cert_req.info.attributes.len(), 1);
+
+        let key = CosignVerificationKey::from_der(
+            &cert_req.info.public_key.to_der().unwrap(),
+            &signing_scheme,
+        )
+        .unwrap();
+        key.verify_signature(
+            Signature::Raw(cert_req.signature.raw_bytes()),
+            &cert_req.info.to_der().unwrap(),
+        )
+        .expect("request signature does not verify");
+    }
+
+    #[test]
+    fn message_hasher_follows_scheme() {
+        let digest = |scheme| {
+            let mut hasher = MessageHasher::new(&scheme).expect("unsupported scheme");
+            io::copy(&mut &b"hello"[..], &mut hasher).unwrap();
+            hasher.finalize()
+        };
+
+        let digest_384 = digest(SigningScheme::ECDSA_P384_SHA384_ASN1);
+        assert_eq!(digest_384.digest, Sha384::digest(b"hello").to_vec());
+        assert!(matches!(
+            digest_384.algorithm.rekor_algorithm(),
+            hashedrekord::AlgorithmKind::sha384
+        ));
+        assert_eq!(
+            digest(SigningScheme::RSA_PKCS1_SHA512(2048)).digest,
+            Sha512::digest(b"hello").to_vec()
+        );
+        assert!(MessageHasher::new(&SigningScheme::ED25519).is_err());
+    }
+
+    #[tokio::test]
+    async fn retry_uploads() {
+        let retry = Retry {
+            max_retries: 2,
+            backoff: Duration::from_millis(1),
+        };
+        let attempts = &std::cell::Cell::new(0);
+        let upload = |failures: u32, status: StatusCode| {
+            attempts.set(0);
+            retry.run(move || {
+                attempts.set(attempts.get() + 1);
+                let succeeds = attempts.get() > failures;
+                async move {
+                    match succeeds {
+                        true => Ok(()),
+                        false => Err(Box::new(RekorError::Response {
+                            status,
+                            message: "unavailable".into(),
+                        }))?,
+                    }
+                }
+            })
+        };
+
+        assert!(upload(0, StatusCode::SERVICE_UNAVAILABLE).await.is_ok());
+        assert_eq!(attempts.get(), 1);
+        assert!(upload(2, StatusCode::SERVICE_UNAVAILABLE).await.is_ok());
+        assert_eq!(attempts.get(), 3);
+        assert!(upload(3, StatusCode::SERVICE_UNAVAILABLE).await.is_err());
+        assert_eq!(attempts.get(), 3);
+
+        // Client errors are not transient.
+        assert!(upload(1, StatusCode::BAD_REQUEST).await.is_err());
+        assert_eq!(attempts.get(), 1);
+    }
+
+    /// A Fulcio instance issuing certificates for the public key of each request.
+    struct FakeFulcio {
+        url: Url,
+        issued: Arc<AtomicUsize>,
+    }
+
+    impl FakeFulcio {
+        /// Starts a fake Fulcio, issuing certificates valid for the duration returned by
+        /// `validity`, given the number of certificates issued before.
+        fn new<V>(validity: V) -> Self
+        where
+            V: Fn(usize) -> chrono::Duration + Send + Sync + 'static,
+        {
+            let ca = generate_certificate(None, CertGenerationOptions::default())
+                .expect("failed to generate CA");
+            let issued = Arc::new(AtomicUsize::new(0));
+
+            let url = serve({
+                let issued = issued.clone();
+                move |request| {
+                    let request: Value =
+                        serde_json::from_slice(&request.body).expect("malformed request");
+                    let csr = request["certificateSigningRequest"]
+                        .as_str()
+                        .and_then(|csr| base64.decode(csr).ok())
+                        .expect("missing CSR");
+                    let csr = CertReq::from_pem(csr).expect("malformed CSR");
+                    let spki = csr.info.public_key.to_der().expect("malformed public key");
+                    let public_key =
+                        PKey::public_key_from_der(&spki).expect("unsupported public key");
+
+                    let validity = validity(issued.fetch_add(1, Ordering::SeqCst));
+                    let leaf = generate_certificate(
+                        Some(&ca),
+                        CertGenerationOptions {
+                            subject_email: Some("user@example.com".into()),
+                            not_after: chrono::Utc::now() + validity,
+                            public_key,
+                            ..Default::default()
+                        },
+                    )
+                    .expect("failed to issue certificate");
+
+                    let certificates = [leaf.cert, ca.cert.clone()]
+                        .map(|cert| String::from_utf8(cert.to_pem().unwrap()).unwrap());
+                    let response = json!({
+                        "signedCertificateEmbeddedSct": {
+                            "chain": { "certificates": certificates },
+                        },
+                    });
+                    (201, response.to_string())
+                }
+            });
+
+            Self {
+                url: Url::parse(&url).expect("failed to parse Fulcio URL"),
+                issued,
+            }
+        }
+
+        /// Returns the number of certificates issued.
+        fn issued(&self) -> usize {
+            self.issued.load(Ordering::SeqCst)
+        }
+    }
+
+    fn signing_context(fulcio: &FakeFulcio, rekor: &FakeRekor) -> SigningContext {
+        let fulcio = FulcioClient::new(
+            fulcio.url.clone(),
+            TokenProvider::Oauth(OauthTokenProvider::default()),
+        );
+
+        SigningContext::new(fulcio, rekor.configuration()).without_sct_verification()
+    }
+
+    /// Returns an unsigned identity token for `user@example.com`, expiring at `exp`.
+    fn identity_token(exp: chrono::DateTime<chrono::Utc>) -> IdentityToken {
+        let claims = json!({
+            "aud": "sigstore",
+            "exp": exp.timestamp(),
+            "email": "user@example.com",
+        });
+        let claims = base64::engine::general_purpose::STANDARD_NO_PAD.encode(claims.to_string());
+
+        IdentityToken::try_from(format!("e30.{claims}.").as_str()).expect("malformed token")
+    }
+
+    async fn sign_batch(
+        rekor: &FakeRekor,
+        inputs: &[&'static [u8]],
+    ) -> Vec<SigstoreResult<SigningArtifact>> {
+        let fulcio = FakeFulcio::new(|_| chrono::Duration::minutes(10));
+        let context = signing_context(&fulcio, rekor);
+        let session = context
+            .signer(identity_token(
+                chrono::Utc::now() + chrono::Duration::minutes(10),
+            ))
+            .await
+            .expect("failed to create session");
+        let options = BatchSigningOptions {
+            max_concurrency: inputs.len(),
+            retry_backoff: Duration::from_millis(1),
+            ..Default::default()
+        };
+
+        session.sign_many(inputs.iter().copied(), &options).await
+    }
+
+    /// Returns the hex-encoded digest of the artifact recorded by a proposed `hashedrekord`.
+    fn proposed_digest(proposed: &Value) -> &str {
+        proposed["spec"]["data"]["hash"]["value"]
+            .as_str()
+            .unwrap_or_default()
+    }
+
+    #[tokio::test]
+    async fn sign_many_isolates_failures() {
+        let rejected = hex::encode(Sha256::digest(b"rejected"));
+        let rekor = FakeRekor::with_uploads(move |proposed, _| match proposed_digest(proposed) {
+            digest if digest == rejected => Upload::Reject(400),
+            _ => Upload::Accept,
+        });
+        let inputs: [&'static [u8]; 4] = [b"first", b"rejected", b"third", b"fourth"];
+
+        let results = sign_batch(&rekor, &inputs).await;
+
+        assert_eq!(results.len(), inputs.len());
+        for (input, result) in inputs.iter().zip(&results) {
+            match (*input, result) {
+                (b"rejected", result) => assert!(matches!(
+                    result,
+                    Err(SigstoreError::RekorError(err)) if err.status() == Some(StatusCode::BAD_REQUEST)
+                )),
+                (input, Ok(artifact)) => assert!(matches!(
+                    &artifact.content,
+                    SignedContent::MessageSignature { input_digest, .. }
+                        if input_digest.digest == Sha256::digest(input).to_vec()
+                )),
+                (_, Err(err)) => panic!("failed to sign: {err}"),
+            }
+        }
+        // The rejected upload is not retried.
+        assert_eq!(rekor.uploads(), inputs.len());
+    }
+
+    #[tokio::test]
+    async fn sign_many_retries_lost_uploads() {
+        // The first upload of every entry is recorded, but its response is lost.
+        let rekor = FakeRekor::with_uploads(|_, attempt| match attempt {
+            1 => Upload::AcceptAndFail(504),
+            _ => Upload::Accept,
+        });
+        let inputs: [&'static [u8]; 2] = [b"first", b"second"];
+
+        let results = sign_batch(&rekor, &inputs).await;
+
+        for result in results {
+            let artifact = result.expect("failed to sign");
+            assert!(artifact.log_entry.is_some());
+        }
+        // Retries conflict with the recorded entries, which are fetched instead.
+        assert_eq!(rekor.uploads(), 2 * inputs.len());
+    }
+
+    #[tokio::test]
+    async fn sign_dsse_recovers_lost_uploads() {
+        let rekor = FakeRekor::with_uploads(|_, attempt| match attempt {
+            1 => Upload::AcceptAndFail(504),
+            _ => Upload::Accept,
+        });
+        let fulcio = FakeFulcio::new(|_| chrono::Duration::minutes(10));
+        let context = signing_context(&fulcio, &rekor);
+        let session = context
+            .signer(identity_token(
+                chrono::Utc::now() + chrono::Duration::minutes(10),
+            ))
+            .await
+            .expect("failed to create session");
+
+        assert!(session
+            .sign_dsse("text/plain", b"payload".to_vec())
+            .await
+            .is_err());
+        let artifact = session
+            .sign_dsse("text/plain", b"payload".to_vec())
+            .await
+            .expect("failed to sign");
+
+        assert!(artifact.log_entry.is_some());
+        assert_eq!(rekor.uploads(), 2);
+    }
+
+    #[tokio::test]
+    async fn sign_rejects_unrelated_conflicting_entries() {
+        let rekor = FakeRekor::new();
+        assert!(sign_batch(&rekor, &[b"other"]).await[0].is_ok());
+        let response = reqwest::get(format!(
+            "{}/api/v1/log/entries?logIndex=0",
+            rekor.configuration().base_path
+        ))
+        .await
+        .and_then(|response| response.error_for_status())
+        .expect("failed to fetch entry")
+        .text()
+        .await
+        .expect("failed to read entry");
+        let uuid = serde_json::from_str::<serde_json::Map<String, Value>>(&response)
+            .ok()
+            .and_then(|entries| entries.keys().next().cloned())
+            .expect("malformed entry");
+
+        // A log claiming every upload conflicts with the entry of another signature.
+        let url = serve(move |request| match request.method.as_str() {
+            "POST" => {
+                let message = format!(
+                    "An equivalent entry already exists in the transparency log with UUID {uuid}"
+                );
+                (409, json!({"code": 409, "message": message}).to_string())
+            }
+            _ => (200, response.clone()),
+        });
+        let fulcio = FakeFulcio::new(|_| chrono::Duration::minutes(10));
+        let fulcio = FulcioClient::new(
+            fulcio.url.clone(),
+            TokenProvider::Oauth(OauthTokenProvider::default()),
+        );
+        let context = SigningContext::new(
+            fulcio,
+            RekorConfiguration {
+                base_path: url,
+                ..Default::default()
+            },
+        )
+        .without_sct_verification();
+        let session = context
+            .signer(identity_token(
+                chrono::Utc::now() + chrono::Duration::minutes(10),
+            ))
+            .await
+            .expect("failed to create session");
+
+        assert!(matches!(
+            session.sign(&b"input"[..]).await,
+            Err(SigstoreError::RekorClientError(_))
+        ));
+    }
+
+    /// An identity token source issuing tokens valid for `lifetime`.
+    struct TokenSource {
+        lifetime: chrono::Duration,
+        issued: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl IdentityTokenSource for TokenSource {
+        async fn identity_token(&self) -> SigstoreResult<IdentityToken> {
+            self.issued.fetch_add(1, Ordering::SeqCst);
+            Ok(identity_token(chrono::Utc::now() + self.lifetime))
+        }
+    }
+
+    #[tokio::test]
+    async fn refreshing_session_renews_identity_once() {
+        // The first certificate is about to expire, later ones are not.
+        let fulcio = FakeFulcio::new(|issued| match issued {
+            0 => chrono::Duration::seconds(30),
+            _ => chrono::Duration::minutes(10),
+        });
+        let rekor = FakeRekor::new();
+        let context = signing_context(&fulcio, &rekor);
+        let tokens = Arc::new(AtomicUsize::new(0));
+        let session = context
+            .refreshing_signer(TokenSource {
+                lifetime: chrono::Duration::minutes(10),
+                issued: tokens.clone(),
+            })
+            .await
+            .expect("failed to create session");
+        assert!(!session.is_expired());
+
+        // Concurrent signing operations share a single renewed identity.
+        let inputs: [&'static [u8]; 8] = [b"0", b"1", b"2", b"3", b"4", b"5", b"6", b"7"];
+        let options = BatchSigningOptions {
+            max_concurrency: inputs.len(),
+            ..Default::default()
+        };
+        for result in session.sign_many(inputs, &options).await {
+            result.expect("failed to sign");
+        }
+
+        assert_eq!(tokens.load(Ordering::SeqCst), 2);
+        assert_eq!(fulcio.issued(), 2);
+    }
+
+    #[tokio::test]
+    async fn refreshing_session_outlives_identity_token() {
+        let fulcio = FakeFulcio::new(|_| chrono::Duration::minutes(10));
+        let rekor = FakeRekor::new();
+        let context = signing_context(&fulcio, &rekor);
+        let tokens = Arc::new(AtomicUsize::new(0));
+        let session = context
+            .refreshing_signer(TokenSource {
+                lifetime: chrono::Duration::minutes(-1),
+                issued: tokens.clone(),
+            })
+            .await
+            .expect("failed to create session");
+
+        // The certificate is valid, so the expired token is not replaced.
+        assert!(!session.is_expired());
+        session.sign(&b"hello"[..]).await.expect("failed to sign");
+
+        assert_eq!(tokens.load(Ordering::SeqCst), 1);
+        assert_eq!(fulcio.issued(), 1);
+
+        // Sessions without a token source expire with their token.
+        let session = context
+            .signer(identity_token(
+                chrono::Utc::now() - chrono::Duration::minutes(1),
+            ))
+            .await
+            .expect("failed to create session");
+        assert!(session.is_expired());
+    }
+
+    #[test]
+    fn from_signing_config_selectors() {
+        let service = |url: &str| Service {
+            url: Url::parse(url).expect("failed to parse URL"),
+            major_api_version: 1,
+            valid_for: Default::default(),
+            operator: String::new(),
+        };
+        let signing_config = |tlog_selector, tsa_selector| SigningConfig {
+            ca_urls: vec![service("https://fulcio.example.com")],
+            oidc_urls: vec![service("https://oauth2.example.com/auth")],
+            tlog_urls: vec![
+                service("https://rekor.example.com"),
+                service("https://rekor2.example.com"),
+            ],
+            tsa_urls: vec![service("https://timestamp.example.com")],
+            tlog_selector,
+            tsa_selector,
+        };
+        let from_signing_config = |tlog_selector, tsa_selector| {
+            SigningContext::from_signing_config(
+                &signing_config(tlog_selector, tsa_selector),
+                &ManualTrustRoot::default(),
+            )
+        };
+
+        for (tlog_selector, tsa_selector) in [
+            (ServiceSelector::Any, ServiceSelector::Any),
+            (ServiceSelector::Exact(1), ServiceSelector::All),
+            (ServiceSelector::Any, ServiceSelector::Exact(1)),
+        ] {
+            assert!(from_signing_config(tlog_selector, tsa_selector).is_ok());
+        }
+        for (tlog_selector, tsa_selector) in [
+            (ServiceSelector::All, ServiceSelector::Any),
+            (ServiceSelector::Exact(2), ServiceSelector::Any),
+            (ServiceSelector::Any, ServiceSelector::Exact(0)),
+        ] {
+            assert!(matches!(
+                from_signing_config(tlog_selector, tsa_selector),
+                Err(SigstoreError::SigningConfigError(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn signing_requires_sct_verification() {
+        let fulcio = FakeFulcio::new(|_| chrono::Duration::minutes(10));
+        let rekor = FakeRekor::new();
+        let context = SigningContext::new(
+            FulcioClient::new(
+                fulcio.url.clone(),
+                TokenProvider::Oauth(OauthTokenProvider::default()),
+            ),
+            rekor.configuration(),
+        );
+
+        let err = context
+            .signer(identity_token(
+                chrono::Utc::now() + chrono::Duration::minutes(10),
+            ))
+            .await
+            .err()
+            .expect("signed without verifying SCTs");
+        assert!(matches!(err, SigstoreError::CtfeKeyringMissingError));
+        assert_eq!(fulcio.issued(), 0);
+    }
+
+    #[cfg(feature = "sigstore-trust-root")]
+    #[test]
+    fn production_verifies_scts() {
+        let context = SigningContext::production().expect("failed to create context");
+
+        assert!(matches!(
+            context.sct_verification,
+            SctVerification::Keyring(_)
+        ));
+    }
+}
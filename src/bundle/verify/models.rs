@@ -16,22 +16,34 @@
 use std::str::FromStr;
 
 use crate::{
-    bundle::{models::Version as BundleVersion, Bundle},
-    crypto::certificate::{is_leaf, is_root_ca, CertificateValidationError},
-    rekor::models as rekor,
+    bundle::{
+        models::{DigestAlgorithm, Version as BundleVersion},
+        Bundle,
+    },
+    crypto::{
+        certificate::{is_leaf, is_root_ca, CertificateValidationError},
+        keyring::KeyringError,
+        merkle::MerkleProofError,
+        timestamp::TimestampError,
+        transparency::SCTError,
+    },
+    rekor::models::{self as rekor, checkpoint::CheckpointError},
 };
 
 use base64::{engine::general_purpose::STANDARD as base64, Engine as _};
-use sigstore_protobuf_specs::dev::sigstore::{
-    bundle::v1::{bundle, verification_material},
-    rekor::v1::{InclusionProof, TransparencyLogEntry},
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use sigstore_protobuf_specs::{
+    dev::sigstore::{
+        bundle::v1::{bundle, verification_material},
+        common::v1::{HashAlgorithm, X509Certificate},
+        rekor::v1::{InclusionProof, TransparencyLogEntry},
+    },
+    io::intoto::Envelope,
 };
 use thiserror::Error;
-use tracing::{debug, error, warn};
-use x509_cert::{
-    der::{Decode, EncodePem},
-    Certificate,
-};
+use tracing::{debug, error};
+use x509_cert::{der::Decode, Certificate};
 
 use super::policy::PolicyError;
 
@@ -50,6 +62,12 @@ pub enum Bundle02ProfileErrorKind {
     CheckpointMissing,
 }
 
+#[derive(Error, Debug)]
+pub enum Bundle03ProfileErrorKind {
+    #[error("bundle must contain a single certificate, not a certificate chain")]
+    CertificateChainForbidden,
+}
+
 #[derive(Error, Debug)]
 #[error(transparent)]
 pub enum BundleProfileErrorKind {
@@ -57,6 +75,8 @@ pub enum BundleProfileErrorKind {
 
     Bundle02Profile(#[from] Bundle02ProfileErrorKind),
 
+    Bundle03Profile(#[from] Bundle03ProfileErrorKind),
+
     #[error("unknown bundle profile {0}")]
     Unknown(String),
 }
@@ -84,10 +104,31 @@ pub enum BundleErrorKind {
     #[error("bundle does not contain signature")]
     SignatureMissing,
 
-    #[error("bundle includes unsupported DSSE signature")]
-    DsseUnsupported,
+    #[error("unsupported message digest algorithm {0}")]
+    DigestAlgorithmUnsupported(i32),
+
+    #[error("bundle's message is hashed with {bundle}, not {input}")]
+    DigestAlgorithmMismatch { bundle: String, input: String },
+
+    #[error("bundle contains a DSSE envelope, not a message signature")]
+    DsseUnexpected,
 
-    #[error("bundle needs 1 tlog entry, got {0}")]
+    #[error("bundle contains a message signature, not a DSSE envelope")]
+    MessageSignatureUnexpected,
+
+    #[error("DSSE envelope needs 1 signature, got {0}")]
+    DsseSignatures(usize),
+
+    #[error("unsupported DSSE payload type {0}")]
+    DssePayloadTypeUnsupported(String),
+
+    #[error("DSSE payload is not a valid in-toto statement")]
+    StatementMalformed(#[source] serde_json::Error),
+
+    #[error("unsupported in-toto statement type {0}")]
+    StatementTypeUnsupported(String),
+
+    #[error("bundle needs at least 1 tlog entry or signed timestamp, got {0} tlog entries")]
     TlogEntry(usize),
 
     #[error(transparent)]
@@ -104,6 +145,9 @@ pub enum CertificateErrorKind {
 
     #[error("certificate verification failed")]
     VerificationFailed(#[source] webpki::Error),
+
+    #[error("certificate SCT verification failed")]
+    Sct(#[source] SCTError),
 }
 
 #[derive(Error, Debug)]
@@ -118,6 +162,57 @@ pub enum SignatureErrorKind {
     Transparency,
 }
 
+#[derive(Error, Debug)]
+pub enum TransparencyErrorKind {
+    #[error("inclusion proof is malformed")]
+    InclusionProofMalformed,
+
+    #[error("inclusion proof verification failed")]
+    InclusionProof(#[source] MerkleProofError),
+
+    #[error("checkpoint verification failed")]
+    Checkpoint(#[source] CheckpointError),
+
+    #[error("log entry is missing its inclusion proof")]
+    InclusionProofMissing,
+
+    #[error("inclusion proof fetched from the transparency log is missing its checkpoint")]
+    CheckpointMissing,
+
+    #[error("bundle contains log entry {log_index} of log {log_id} more than once")]
+    DuplicateEntry { log_id: String, log_index: i64 },
+
+    #[error("bundle needs {threshold} tlog entries, got {entries}")]
+    EntriesBelowThreshold { threshold: usize, entries: usize },
+
+    #[error("log entry could not be fetched from the transparency log: {0}")]
+    LogEntryFetch(String),
+
+    #[error("log entry is malformed")]
+    LogEntryMalformed,
+
+    #[error("log entry does not match the transparency log's")]
+    LogEntryMismatch,
+
+    #[error("log entry is missing its log ID")]
+    LogIdMissing,
+
+    #[error("signed entry timestamp payload could not be constructed")]
+    SignedEntryTimestampPayload,
+
+    #[error("signed entry timestamp verification failed")]
+    SignedEntryTimestamp(#[source] KeyringError),
+}
+
+#[derive(Error, Debug)]
+pub enum TimestampErrorKind {
+    #[error("signed timestamp verification failed")]
+    VerificationFailed(#[source] TimestampError),
+
+    #[error("signed timestamp is outside of the signing certificate's validity period")]
+    OutsideValidityPeriod,
+}
+
 #[derive(Error, Debug)]
 #[error(transparent)]
 pub enum VerificationError {
@@ -130,81 +225,129 @@ pub enum VerificationError {
 
     Signature(#[from] SignatureErrorKind),
 
+    Transparency(#[from] TransparencyErrorKind),
+
+    Timestamp(#[from] TimestampErrorKind),
+
     Policy(#[from] PolicyError),
 }
 
 pub type VerificationResult = Result<(), VerificationError>;
 
+/// The signed content of a bundle.
+pub(crate) enum SignedContent {
+    /// A signature over the digest of an artifact, hashed with `digest_algorithm`.
+    MessageSignature {
+        signature: Vec<u8>,
+        digest_algorithm: DigestAlgorithm,
+    },
+    /// A DSSE envelope, holding exactly one signature.
+    DsseEnvelope(Envelope),
+}
+
+/// The material to verify the signature of a bundle with.
+pub(crate) enum SigningMaterial {
+    /// The signing (leaf) certificate.
+    Certificate(Box<Certificate>),
+    /// A public key distributed out of band. The bundle may only carry a hint identifying it.
+    PublicKey,
+}
+
 pub struct CheckedBundle {
-    pub(crate) certificate: Certificate,
-    pub(crate) signature: Vec<u8>,
+    pub(crate) material: SigningMaterial,
+    pub(crate) content: SignedContent,
 
-    tlog_entry: TransparencyLogEntry,
+    tlog_entries: Vec<TransparencyLogEntry>,
+    timestamps: Vec<Vec<u8>>,
 }
 
 impl TryFrom<Bundle> for CheckedBundle {
     type Error = BundleErrorKind;
 
     fn try_from(input: Bundle) -> Result<Self, Self::Error> {
-        let (content, mut tlog_entries) = match input.verification_material {
-            Some(m) => (m.content, m.tlog_entries),
+        let (content, tlog_entries, timestamps) = match input.verification_material {
+            Some(m) => (
+                m.content,
+                m.tlog_entries,
+                m.timestamp_verification_data
+                    .map(|data| data.rfc3161_timestamps)
+                    .unwrap_or_default(),
+            ),
             _ => return Err(BundleErrorKind::VerificationMaterialMissing),
         };
 
         // Parse the certificates. The first entry in the chain MUST be a leaf certificate, and the
         // rest of the chain MUST NOT include a root CA or any intermediate CAs that appear in an
-        // independent root of trust.
-        let certs = match content {
-            Some(verification_material::Content::X509CertificateChain(ch)) => ch.certificates,
-            Some(verification_material::Content::Certificate(cert)) => {
-                vec![cert]
+        // independent root of trust. Bundles signed with a public key carry no certificates.
+        let (material, is_chain) = match content {
+            Some(verification_material::Content::X509CertificateChain(ch)) => (
+                SigningMaterial::Certificate(Box::new(leaf_certificate(&ch.certificates)?)),
+                true,
+            ),
+            Some(verification_material::Content::Certificate(cert)) => (
+                SigningMaterial::Certificate(Box::new(leaf_certificate(&[cert])?)),
+                false,
+            ),
+            Some(verification_material::Content::PublicKey(_)) => {
+                (SigningMaterial::PublicKey, false)
             }
-            _ => return Err(BundleErrorKind::VerificationMaterialContentUnsupported),
-        };
-        let certs = certs
-            .iter()
-            .map(|c| c.raw_bytes.as_slice())
-            .map(Certificate::from_der)
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(BundleErrorKind::CertificateMalformed)?;
-
-        let [leaf_cert, chain_certs @ ..] = &certs[..] else {
-            return Err(BundleErrorKind::CertificatesMissing);
+            None => return Err(BundleErrorKind::VerificationMaterialContentUnsupported),
         };
 
-        is_leaf(leaf_cert).map_err(BundleErrorKind::NoLeaf)?;
-
-        for chain_cert in chain_certs {
-            if is_root_ca(chain_cert).is_ok() {
-                return Err(BundleErrorKind::RootInChain);
+        let content = match input.content.ok_or(BundleErrorKind::SignatureMissing)? {
+            bundle::Content::MessageSignature(s) => {
+                // Bundles without a message digest predate other algorithms, and use SHA-256.
+                let digest_algorithm = match s.message_digest {
+                    Some(digest) => HashAlgorithm::try_from(digest.algorithm)
+                        .ok()
+                        .and_then(DigestAlgorithm::from_bundle_algorithm)
+                        .ok_or(BundleErrorKind::DigestAlgorithmUnsupported(
+                            digest.algorithm,
+                        ))?,
+                    None => DigestAlgorithm::Sha256,
+                };
+                SignedContent::MessageSignature {
+                    signature: s.signature,
+                    digest_algorithm,
+                }
+            }
+            bundle::Content::DsseEnvelope(envelope) => {
+                if envelope.signatures.len() != 1 {
+                    return Err(BundleErrorKind::DsseSignatures(envelope.signatures.len()));
+                }
+                SignedContent::DsseEnvelope(envelope)
             }
-        }
-
-        let signature = match input.content.ok_or(BundleErrorKind::SignatureMissing)? {
-            bundle::Content::MessageSignature(s) => s.signature,
-            _ => return Err(BundleErrorKind::DsseUnsupported),
         };
 
-        if tlog_entries.len() != 1 {
-            return Err(BundleErrorKind::TlogEntry(tlog_entries.len()));
+        // Bundles must carry some evidence of the time of signing.
+        if tlog_entries.is_empty() && timestamps.is_empty() {
+            return Err(BundleErrorKind::TlogEntry(0));
         }
-        let tlog_entry = tlog_entries.remove(0);
 
-        let (inclusion_promise, inclusion_proof) =
-            (&tlog_entry.inclusion_promise, &tlog_entry.inclusion_proof);
+        let version = BundleVersion::from_str(&input.media_type)
+            .or(Err(BundleProfileErrorKind::Unknown(input.media_type)))?;
+
+        // v0.3 bundles carry the same transparency materials as v0.2 bundles, but MUST NOT
+        // carry a certificate chain: intermediates are distributed with the trust root.
+        if version == BundleVersion::Bundle0_3 && is_chain {
+            error!("bundle must contain a single certificate");
+            return Err(BundleProfileErrorKind::from(
+                Bundle03ProfileErrorKind::CertificateChainForbidden,
+            ))?;
+        }
 
         // `inclusion_proof` is a required field in the current protobuf spec,
         // but older versions of Rekor didn't provide it. Check invariants
         // here and selectively allow for this case.
         //
         // https://github.com/sigstore/sigstore-python/pull/634#discussion_r1182769140
-        let check_01_bundle = || -> Result<(), BundleProfileErrorKind> {
-            if inclusion_promise.is_none() {
+        let check_01_bundle = |entry: &TransparencyLogEntry| -> Result<(), BundleProfileErrorKind> {
+            if entry.inclusion_promise.is_none() {
                 return Err(Bundle01ProfileErrorKind::InclusionPromiseMissing)?;
             }
 
             if matches!(
-                inclusion_proof,
+                entry.inclusion_proof,
                 Some(InclusionProof {
                     checkpoint: None,
                     ..
@@ -215,14 +358,14 @@ impl TryFrom<Bundle> for CheckedBundle {
 
             Ok(())
         };
-        let check_02_bundle = || -> Result<(), BundleProfileErrorKind> {
-            if inclusion_proof.is_none() {
+        let check_02_bundle = |entry: &TransparencyLogEntry| -> Result<(), BundleProfileErrorKind> {
+            if entry.inclusion_proof.is_none() {
                 error!("bundle must contain inclusion proof");
                 return Err(Bundle02ProfileErrorKind::InclusionProofMissing)?;
             }
 
             if matches!(
-                inclusion_proof,
+                entry.inclusion_proof,
                 Some(InclusionProof {
                     checkpoint: None,
                     ..
@@ -234,58 +377,394 @@ impl TryFrom<Bundle> for CheckedBundle {
 
             Ok(())
         };
-        match BundleVersion::from_str(&input.media_type) {
-            Ok(BundleVersion::Bundle0_1) => check_01_bundle()?,
-            Ok(BundleVersion::Bundle0_2) => check_02_bundle()?,
-            Err(_) => return Err(BundleProfileErrorKind::Unknown(input.media_type))?,
+        for entry in &tlog_entries {
+            match version {
+                BundleVersion::Bundle0_1 => check_01_bundle(entry)?,
+                BundleVersion::Bundle0_2 | BundleVersion::Bundle0_3 => check_02_bundle(entry)?,
+            }
         }
 
         Ok(Self {
-            certificate: leaf_cert.clone(),
-            signature,
-            tlog_entry,
+            material,
+            content,
+            tlog_entries,
+            timestamps: timestamps
+                .into_iter()
+                .map(|timestamp| timestamp.signed_timestamp)
+                .collect(),
         })
     }
 }
 
+/// Parses a bundle's certificate chain, returning its leaf certificate.
+fn leaf_certificate(certs: &[X509Certificate]) -> Result<Certificate, BundleErrorKind> {
+    let certs = certs
+        .iter()
+        .map(|c| c.raw_bytes.as_slice())
+        .map(Certificate::from_der)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(BundleErrorKind::CertificateMalformed)?;
+
+    let [leaf_cert, chain_certs @ ..] = &certs[..] else {
+        return Err(BundleErrorKind::CertificatesMissing);
+    };
+
+    is_leaf(leaf_cert).map_err(BundleErrorKind::NoLeaf)?;
+
+    for chain_cert in chain_certs {
+        if is_root_ca(chain_cert).is_ok() {
+            return Err(BundleErrorKind::RootInChain);
+        }
+    }
+
+    Ok(leaf_cert.clone())
+}
+
 impl CheckedBundle {
-    /// Retrieves and checks consistency of the bundle's [TransparencyLogEntry].
-    pub fn tlog_entry(&self, offline: bool, input_digest: &[u8]) -> Option<&TransparencyLogEntry> {
-        let base64_pem_certificate =
-            base64.encode(self.certificate.to_pem(pkcs8::LineEnding::LF).ok()?);
-
-        let expected_entry = rekor::Hashedrekord {
-            kind: "hashedrekord".to_owned(),
-            api_version: "0.0.1".to_owned(),
-            spec: rekor::hashedrekord::Spec {
-                signature: rekor::hashedrekord::Signature {
-                    content: base64.encode(&self.signature),
-                    public_key: rekor::hashedrekord::PublicKey::new(base64_pem_certificate),
+    /// Returns the signature bytes of the bundle's content.
+    pub fn signature(&self) -> &[u8] {
+        match &self.content {
+            SignedContent::MessageSignature { signature, .. } => signature,
+            SignedContent::DsseEnvelope(envelope) => &envelope.signatures[0].sig,
+        }
+    }
+
+    /// Returns the bundle's DER-encoded RFC 3161 signed timestamps.
+    pub fn timestamps(&self) -> &[Vec<u8>] {
+        &self.timestamps
+    }
+
+    /// Returns the bundle's [TransparencyLogEntry]s. Bundles without any also carry signed
+    /// timestamps.
+    pub fn tlog_entries(&self) -> &[TransparencyLogEntry] {
+        &self.tlog_entries
+    }
+
+    /// The hash function the signed message is digested with. DSSE envelopes are signed over
+    /// their pre-authentication encoding, and report SHA-256.
+    pub(crate) fn digest_algorithm(&self) -> DigestAlgorithm {
+        match self.content {
+            SignedContent::MessageSignature {
+                digest_algorithm, ..
+            } => digest_algorithm,
+            SignedContent::DsseEnvelope(_) => DigestAlgorithm::Sha256,
+        }
+    }
+
+    /// Checks consistency of one of the bundle's [TransparencyLogEntry]s with its other signing
+    /// materials, and with `verifier`: the PEM-encoded certificate or public key the entry must
+    /// have been uploaded with.
+    ///
+    /// `input_digest` is the digest of the signed artifact for message signatures, and must be
+    /// `None` for DSSE envelopes.
+    pub fn is_consistent(
+        &self,
+        entry: &TransparencyLogEntry,
+        input_digest: Option<&[u8]>,
+        verifier: &str,
+    ) -> bool {
+        self.check_consistency(entry, input_digest, verifier)
+            .unwrap_or(false)
+    }
+
+    fn check_consistency(
+        &self,
+        entry: &TransparencyLogEntry,
+        input_digest: Option<&[u8]>,
+        verifier: &str,
+    ) -> Option<bool> {
+        let base64_pem_verifier = base64.encode(verifier);
+        let actual: serde_json::Value = serde_json::from_slice(&entry.canonicalized_body).ok()?;
+
+        let consistent = match (&self.content, input_digest) {
+            (
+                SignedContent::MessageSignature {
+                    signature,
+                    digest_algorithm,
                 },
-                data: rekor::hashedrekord::Data {
-                    hash: rekor::hashedrekord::Hash {
-                        algorithm: rekor::hashedrekord::AlgorithmKind::sha256,
-                        value: hex::encode(input_digest),
+                Some(input_digest),
+            ) => {
+                let expected_entry = rekor::Hashedrekord {
+                    kind: "hashedrekord".to_owned(),
+                    api_version: "0.0.1".to_owned(),
+                    spec: rekor::hashedrekord::Spec {
+                        signature: rekor::hashedrekord::Signature {
+                            content: base64.encode(signature),
+                            public_key: rekor::hashedrekord::PublicKey::new(base64_pem_verifier),
+                        },
+                        data: rekor::hashedrekord::Data {
+                            hash: rekor::hashedrekord::Hash {
+                                algorithm: digest_algorithm.rekor_algorithm(),
+                                value: hex::encode(input_digest),
+                            },
+                        },
+                    },
+                };
+
+                actual == serde_json::to_value(expected_entry).ok()?
+            }
+            (SignedContent::DsseEnvelope(envelope), None) => {
+                dsse_entry_consistent(&actual, envelope, base64_pem_verifier)
+            }
+            _ => false,
+        };
+
+        Some(consistent)
+    }
+}
+
+/// Checks that a `dsse` or `intoto` Rekor entry body was produced for `envelope`, signed with
+/// the given certificate or public key.
+///
+/// Rekor does not store the envelope verbatim, so only the payload digest and the signatures are
+/// compared.
+fn dsse_entry_consistent(
+    body: &serde_json::Value,
+    envelope: &Envelope,
+    base64_pem_verifier: String,
+) -> bool {
+    let payload_hash = json!({
+        "algorithm": "sha256",
+        "value": hex::encode(Sha256::digest(&envelope.payload)),
+    });
+    let signature = &envelope.signatures[0].sig;
+    let spec = &body["spec"];
+
+    match (body["kind"].as_str(), body["apiVersion"].as_str()) {
+        (Some("dsse"), Some("0.0.1")) => {
+            spec["payloadHash"] == payload_hash
+                && spec["signatures"]
+                    == json!([{
+                        "signature": base64.encode(signature),
+                        "verifier": base64_pem_verifier,
+                    }])
+        }
+        (Some("intoto"), Some("0.0.2")) => {
+            // The intoto type stores the envelope's signatures base64-encoded twice.
+            let content = &spec["content"];
+            content["payloadHash"] == payload_hash
+                && content["envelope"]["payloadType"] == envelope.payload_type.as_str()
+                && content["envelope"]["signatures"]
+                    == json!([{
+                        "sig": base64.encode(base64.encode(signature)),
+                        "publicKey": base64_pem_verifier,
+                    }])
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use sigstore_protobuf_specs::{
+        dev::sigstore::{
+            bundle::v1::{TimestampVerificationData, VerificationMaterial},
+            common::v1::{MessageSignature, Rfc3161SignedTimestamp, X509CertificateChain},
+            rekor::v1::Checkpoint,
+        },
+        io::intoto::Signature,
+    };
+
+    use super::*;
+    use crate::crypto::tests::{generate_certificate, CertGenerationOptions};
+
+    const PAYLOAD: &[u8] = b"payload";
+    const SIGNATURE: &[u8] = b"signature";
+    const PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+    const VERIFIER: &str = "LS0tLS1CRUdJTiBQVUJMSUMgS0VZLS0tLS0K";
+
+    fn envelope() -> Envelope {
+        Envelope {
+            payload: PAYLOAD.to_vec(),
+            payload_type: PAYLOAD_TYPE.to_owned(),
+            signatures: vec![Signature {
+                sig: SIGNATURE.to_vec(),
+                keyid: String::new(),
+            }],
+        }
+    }
+
+    fn payload_hash(payload: &[u8]) -> serde_json::Value {
+        json!({
+            "algorithm": "sha256",
+            "value": hex::encode(Sha256::digest(payload)),
+        })
+    }
+
+    fn dsse_body(payload: &[u8], signature: &[u8], verifier: &str) -> serde_json::Value {
+        json!({
+            "kind": "dsse",
+            "apiVersion": "0.0.1",
+            "spec": {
+                "envelopeHash": payload_hash(b"envelope"),
+                "payloadHash": payload_hash(payload),
+                "signatures": [{
+                    "signature": base64.encode(signature),
+                    "verifier": verifier,
+                }],
+            },
+        })
+    }
+
+    fn intoto_body(payload: &[u8], signature: &str, payload_type: &str) -> serde_json::Value {
+        json!({
+            "kind": "intoto",
+            "apiVersion": "0.0.2",
+            "spec": {
+                "content": {
+                    "envelope": {
+                        "payloadType": payload_type,
+                        "signatures": [{
+                            "sig": signature,
+                            "publicKey": VERIFIER,
+                        }],
                     },
+                    "payloadHash": payload_hash(payload),
                 },
             },
-        };
+        })
+    }
 
-        let entry = if !offline && self.tlog_entry.inclusion_proof.is_none() {
-            warn!("online rekor fetching is not implemented yet, but is necessary for this bundle");
-            return None;
-        } else {
-            &self.tlog_entry
-        };
+    #[rstest]
+    #[case::dsse(dsse_body(PAYLOAD, SIGNATURE, VERIFIER), true)]
+    #[case::dsse_payload_mismatch(dsse_body(b"other payload", SIGNATURE, VERIFIER), false)]
+    #[case::dsse_signature_mismatch(dsse_body(PAYLOAD, b"other signature", VERIFIER), false)]
+    #[case::dsse_verifier_mismatch(dsse_body(PAYLOAD, SIGNATURE, "b3RoZXIK"), false)]
+    #[case::intoto(
+        intoto_body(PAYLOAD, &base64.encode(base64.encode(SIGNATURE)), PAYLOAD_TYPE),
+        true
+    )]
+    #[case::intoto_payload_mismatch(
+        intoto_body(b"other payload", &base64.encode(base64.encode(SIGNATURE)), PAYLOAD_TYPE),
+        false
+    )]
+    #[case::intoto_signature_encoded_once(
+        intoto_body(PAYLOAD, &base64.encode(SIGNATURE), PAYLOAD_TYPE),
+        false
+    )]
+    #[case::intoto_payload_type_mismatch(
+        intoto_body(PAYLOAD, &base64.encode(base64.encode(SIGNATURE)), "text/plain"),
+        false
+    )]
+    #[case::unknown_version({
+        let mut body = dsse_body(PAYLOAD, SIGNATURE, VERIFIER);
+        body["apiVersion"] = json!("0.0.2");
+        body
+    }, false)]
+    #[case::unknown_kind(json!({"kind": "hashedrekord", "apiVersion": "0.0.1", "spec": {}}), false)]
+    fn dsse_entry_consistency(#[case] body: serde_json::Value, #[case] consistent: bool) {
+        assert_eq!(
+            dsse_entry_consistent(&body, &envelope(), VERIFIER.to_owned()),
+            consistent
+        );
+    }
 
-        let actual: serde_json::Value =
-            serde_json::from_slice(&self.tlog_entry.canonicalized_body).ok()?;
-        let expected: serde_json::Value = serde_json::to_value(expected_entry).ok()?;
+    /// Builds a bundle of the given version, signed by a certificate generated for the test and
+    /// carrying it as either a certificate chain or a single certificate.
+    fn certificate_bundle(version: BundleVersion, chain: bool) -> Bundle {
+        let ca = generate_certificate(None, CertGenerationOptions::default())
+            .expect("failed to generate CA");
+        let leaf = generate_certificate(Some(&ca), CertGenerationOptions::default())
+            .expect("failed to generate certificate");
+        let certificate = X509Certificate {
+            raw_bytes: leaf.cert.to_der().expect("failed to encode certificate"),
+        };
+        let content = match chain {
+            true => verification_material::Content::X509CertificateChain(X509CertificateChain {
+                certificates: vec![certificate],
+            }),
+            false => verification_material::Content::Certificate(certificate),
+        };
 
-        if actual != expected {
-            return None;
+        Bundle {
+            media_type: version.to_string(),
+            verification_material: Some(VerificationMaterial {
+                content: Some(content),
+                tlog_entries: vec![TransparencyLogEntry {
+                    inclusion_proof: Some(InclusionProof {
+                        checkpoint: Some(Checkpoint::default()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }],
+                timestamp_verification_data: None,
+            }),
+            content: Some(bundle::Content::MessageSignature(MessageSignature {
+                message_digest: None,
+                signature: SIGNATURE.to_vec(),
+            })),
         }
+    }
+
+    #[rstest]
+    #[case::v0_2_chain(BundleVersion::Bundle0_2, true)]
+    #[case::v0_2_certificate(BundleVersion::Bundle0_2, false)]
+    #[case::v0_3_certificate(BundleVersion::Bundle0_3, false)]
+    fn certificate_material_accepted(#[case] version: BundleVersion, #[case] chain: bool) {
+        let checked = CheckedBundle::try_from(certificate_bundle(version, chain))
+            .expect("failed to check bundle");
+
+        assert!(matches!(checked.material, SigningMaterial::Certificate(_)));
+    }
+
+    #[test]
+    fn v0_3_certificate_chain_forbidden() {
+        let err = CheckedBundle::try_from(certificate_bundle(BundleVersion::Bundle0_3, true))
+            .err()
+            .expect("v0.3 bundle with a certificate chain was accepted");
+
+        assert!(matches!(
+            err,
+            BundleErrorKind::BundleProfile(BundleProfileErrorKind::Bundle03Profile(
+                Bundle03ProfileErrorKind::CertificateChainForbidden
+            ))
+        ));
+    }
+
+    /// Replaces the tlog entries of `bundle` with a signed timestamp.
+    fn timestamp_only(mut bundle: Bundle) -> Bundle {
+        let material = bundle
+            .verification_material
+            .as_mut()
+            .expect("bundle has no verification material");
+        material.tlog_entries.clear();
+        material.timestamp_verification_data = Some(TimestampVerificationData {
+            rfc3161_timestamps: vec![Rfc3161SignedTimestamp {
+                signed_timestamp: b"timestamp".to_vec(),
+            }],
+        });
+
+        bundle
+    }
+
+    #[test]
+    fn v0_3_certificate_chain_forbidden_without_tlog_entries() {
+        let bundle = timestamp_only(certificate_bundle(BundleVersion::Bundle0_3, true));
+        let err = CheckedBundle::try_from(bundle)
+            .err()
+            .expect("v0.3 bundle with a certificate chain was accepted");
+
+        assert!(matches!(
+            err,
+            BundleErrorKind::BundleProfile(BundleProfileErrorKind::Bundle03Profile(
+                Bundle03ProfileErrorKind::CertificateChainForbidden
+            ))
+        ));
+    }
 
-        Some(entry)
+    #[test]
+    fn unknown_media_type_rejected_without_tlog_entries() {
+        let mut bundle = timestamp_only(certificate_bundle(BundleVersion::Bundle0_3, false));
+        bundle.media_type = "application/vnd.dev.sigstore.bundle+json;version=0.4".to_owned();
+        let err = CheckedBundle::try_from(bundle)
+            .err()
+            .expect("bundle with an unknown media type was accepted");
+
+        assert!(matches!(
+            err,
+            BundleErrorKind::BundleProfile(BundleProfileErrorKind::Unknown(media_type))
+                if media_type.ends_with("version=0.4")
+        ));
     }
 }
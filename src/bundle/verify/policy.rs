@@ -15,11 +15,20 @@
 //! Verification constraints for certificate metadata.
 //!
 //! <https://github.com/sigstore/fulcio/blob/main/docs/oid-info.md#extension-values>
+//!
+//! Bundles signed with a public key rather than a certificate are verified with the
+//! [`PublicKey`] policy instead.
 
 use const_oid::ObjectIdentifier;
+use pkcs8::LineEnding;
 use thiserror::Error;
 use tracing::warn;
+use x509_cert::der::{Decode, DecodePem, Encode, EncodePem};
 use x509_cert::ext::pkix::{name::GeneralName, SubjectAltName};
+use x509_cert::spki::SubjectPublicKeyInfoOwned;
+
+use crate::crypto::{CosignVerificationKey, SigningScheme};
+use crate::errors::Result as SigstoreResult;
 
 macro_rules! oids {
     ($($name:ident = $value:literal),+) => {
@@ -86,6 +95,12 @@ pub enum PolicyError {
 
     #[error("0 of {total} policies succeeded")]
     AnyOf { total: usize },
+
+    #[error("bundle is signed with a certificate, but the policy expects a public key")]
+    CertificateUnexpected,
+
+    #[error("bundle is signed with a public key, but the policy expects a certificate")]
+    PublicKeyUnexpected,
 }
 
 pub type PolicyResult = Result<(), PolicyError>;
@@ -162,6 +177,12 @@ impl_policy!(
 /// An interface that all policies must conform to.
 pub trait VerificationPolicy {
     fn verify(&self, cert: &x509_cert::Certificate) -> PolicyResult;
+
+    /// Returns the public key that bundles must be signed with, for policies verifying bundles
+    /// signed without a certificate. Such policies reject all certificates.
+    fn public_key(&self) -> Option<&PublicKey> {
+        None
+    }
 }
 
 /// The "any of" policy, corresponding to a logical OR between child policies.
@@ -293,3 +314,83 @@ impl VerificationPolicy for Identity {
         Ok(())
     }
 }
+
+/// Verifies that bundles are signed with the given public key, rather than with a certificate.
+///
+/// Bundles signed with a key carry at most a hint identifying it: the key itself must be
+/// distributed out of band.
+pub struct PublicKey {
+    key: CosignVerificationKey,
+    pem: String,
+}
+
+impl PublicKey {
+    /// Creates a policy from a DER-encoded `SubjectPublicKeyInfo`, verifying signatures with
+    /// the given signing scheme.
+    pub fn new(der: &[u8], signing_scheme: &SigningScheme) -> SigstoreResult<Self> {
+        Self::with_key(der, CosignVerificationKey::from_der(der, signing_scheme)?)
+    }
+
+    /// Creates a policy from a DER-encoded `SubjectPublicKeyInfo`, inferring the signing scheme
+    /// from the key. RSA keys are assumed to sign with PKCS#1 v1.5 padding and SHA-256.
+    pub fn from_der(der: &[u8]) -> SigstoreResult<Self> {
+        Self::with_key(der, CosignVerificationKey::try_from_der(der)?)
+    }
+
+    /// Creates a policy from a PEM-encoded `SubjectPublicKeyInfo`, inferring the signing scheme
+    /// from the key. RSA keys are assumed to sign with PKCS#1 v1.5 padding and SHA-256.
+    pub fn from_pem(pem: &[u8]) -> SigstoreResult<Self> {
+        let der = SubjectPublicKeyInfoOwned::from_pem(pem)?.to_der()?;
+
+        Self::from_der(&der)
+    }
+
+    fn with_key(der: &[u8], key: CosignVerificationKey) -> SigstoreResult<Self> {
+        // Transparency log entries hold the key as PEM, the way signers upload it.
+        let pem = SubjectPublicKeyInfoOwned::from_der(der)?.to_pem(LineEnding::LF)?;
+
+        Ok(Self { key, pem })
+    }
+
+    /// Returns the key to verify signatures with.
+    pub(crate) fn verification_key(&self) -> &CosignVerificationKey {
+        &self.key
+    }
+
+    /// Returns the PEM-encoded key.
+    pub(crate) fn pem(&self) -> &str {
+        &self.pem
+    }
+}
+
+impl VerificationPolicy for PublicKey {
+    fn verify(&self, _cert: &x509_cert::Certificate) -> PolicyResult {
+        Err(PolicyError::CertificateUnexpected)
+    }
+
+    fn public_key(&self) -> Option<&PublicKey> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn public_key_policy() {
+        let signer = SigningScheme::ECDSA_P256_SHA256_ASN1
+            .create_signer()
+            .expect("failed to create signer");
+        let key_pair = signer.to_sigstore_keypair().unwrap();
+        let pem = key_pair.public_key_to_pem().unwrap();
+
+        // The key must be encoded the way signers upload it to the transparency log.
+        let policy = PublicKey::from_pem(pem.as_bytes()).expect("failed to parse public key");
+        assert_eq!(policy.pem(), pem);
+        let policy = PublicKey::from_der(&key_pair.public_key_to_der().unwrap())
+            .expect("failed to parse public key");
+        assert_eq!(policy.pem(), pem);
+        assert!(policy.public_key().is_some());
+    }
+}
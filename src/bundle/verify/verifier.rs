@@ -14,19 +14,44 @@
 
 //! Verifiers: async and blocking.
 
-use std::io::{self, Read};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::io::{self, Read, Write};
 
-use sha2::{Digest, Sha256};
+use base64::{engine::general_purpose::STANDARD as base64, Engine as _};
+use const_oid::AssociatedOid;
+use json_syntax::Print;
+use serde_json::json;
+use sha2::Digest;
+use sigstore_protobuf_specs::dev::sigstore::rekor::v1::{
+    Checkpoint, InclusionPromise, InclusionProof, TransparencyLogEntry,
+};
 use tokio::io::{AsyncRead, AsyncReadExt};
 use tracing::debug;
 use webpki::types::{CertificateDer, UnixTime};
-use x509_cert::der::Encode;
+use x509_cert::{
+    der::{Decode, Encode, EncodePem},
+    Certificate,
+};
 
 use crate::{
-    bundle::Bundle,
-    crypto::{CertificatePool, CosignVerificationKey, Signature},
+    bundle::{
+        intoto::{self, Statement},
+        models::{DigestAlgorithm, MessageDigest, MessageHasher},
+        Bundle,
+    },
+    crypto::{
+        keyring::Keyring,
+        merkle,
+        timestamp::{verify_timestamp, TimestampAuthorities},
+        transparency::verify_embedded_sct,
+        CertificatePool, CosignVerificationKey, Signature,
+    },
     errors::Result as SigstoreResult,
-    rekor::apis::configuration::Configuration as RekorConfiguration,
+    rekor::{
+        apis::configuration::Configuration as RekorConfiguration, client::RekorClient,
+        models::SignedCheckpoint,
+    },
     trust::TrustRoot,
 };
 
@@ -34,18 +59,55 @@ use crate::{
 use crate::trust::sigstore::SigstoreTrustRoot;
 
 use super::{
-    models::{CertificateErrorKind, CheckedBundle, SignatureErrorKind},
-    policy::VerificationPolicy,
+    models::{
+        BundleErrorKind, CertificateErrorKind, CheckedBundle, SignatureErrorKind, SignedContent,
+        SigningMaterial, TimestampErrorKind, TransparencyErrorKind,
+    },
+    policy::{PolicyError, VerificationPolicy},
     VerificationError, VerificationResult,
 };
 
+/// Options controlling how a [`Verifier`] treats the transparency log evidence of a bundle.
+#[derive(Clone, Debug)]
+pub struct VerificationOptions {
+    /// Verify using only the materials in the bundle, without contacting the transparency log.
+    /// Defaults to `true`.
+    ///
+    /// When online, log entries that only carry an inclusion promise (SET) are fetched from the
+    /// log, and their current inclusion proof is verified as well.
+    pub offline: bool,
+    /// Require an inclusion proof for every log entry, rather than accepting an inclusion
+    /// promise (SET) on its own.
+    pub require_inclusion_proof: bool,
+    /// The minimum number of transparency log entries the bundle must carry. Every entry is
+    /// verified, regardless of this threshold, and bundles carrying an entry more than once are
+    /// rejected.
+    ///
+    /// A threshold of `0` accepts bundles that were not recorded in the transparency log, whose
+    /// only evidence of the time of signing is their signed timestamps.
+    pub tlog_threshold: usize,
+}
+
+impl Default for VerificationOptions {
+    fn default() -> Self {
+        Self {
+            offline: true,
+            require_inclusion_proof: false,
+            tlog_threshold: 1,
+        }
+    }
+}
+
 /// An asynchronous Sigstore verifier.
 ///
 /// For synchronous usage, see [`Verifier`].
 pub struct Verifier {
-    #[allow(dead_code)]
-    rekor_config: RekorConfiguration,
+    rekor: RekorClient,
     cert_pool: CertificatePool,
+    fulcio_certs: Vec<Certificate>,
+    rekor_keyring: Keyring,
+    ctfe_keyring: Keyring,
+    tsa_authorities: TimestampAuthorities,
 }
 
 impl Verifier {
@@ -56,53 +118,267 @@ impl Verifier {
         rekor_config: RekorConfiguration,
         trust_repo: R,
     ) -> SigstoreResult<Self> {
-        let cert_pool = CertificatePool::from_certificates(trust_repo.fulcio_certs()?, [])?;
+        let fulcio_certs = trust_repo.fulcio_certs()?;
+        let cert_pool = CertificatePool::from_certificates(fulcio_certs.iter().cloned(), [])?;
+        let fulcio_certs = fulcio_certs
+            .iter()
+            .map(|cert| Certificate::from_der(cert))
+            .collect::<Result<_, _>>()?;
+        let rekor_keyring = Keyring::from_log_keys(trust_repo.rekor_log_keys()?)?;
+        let ctfe_keyring = Keyring::from_log_keys(trust_repo.ctfe_log_keys()?)?;
+        let tsa_authorities =
+            TimestampAuthorities::from_authorities(trust_repo.timestamp_authorities()?)?;
 
         Ok(Self {
-            rekor_config,
+            rekor: RekorClient::new(rekor_config),
             cert_pool,
+            fulcio_certs,
+            rekor_keyring,
+            ctfe_keyring,
+            tsa_authorities,
         })
     }
 
     /// Verifies an input digest against the given Sigstore Bundle, ensuring conformance to the
     /// provided [`VerificationPolicy`].
-    pub async fn verify_digest<P>(
+    ///
+    /// The digest must be computed with the hash function recorded in the bundle: SHA-256, SHA-384
+    /// or SHA-512.
+    pub async fn verify_digest<D, P>(
         &self,
-        input_digest: Sha256,
+        input_digest: D,
         bundle: Bundle,
         policy: &P,
-        offline: bool,
+        options: &VerificationOptions,
     ) -> VerificationResult
+    where
+        D: Digest + AssociatedOid,
+        P: VerificationPolicy,
+    {
+        let algorithm = DigestAlgorithm::from_oid(D::OID).ok_or_else(|| {
+            VerificationError::Input(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unsupported digest algorithm {}", D::OID),
+            ))
+        })?;
+        let input_digest = MessageDigest {
+            algorithm,
+            digest: input_digest.finalize().to_vec(),
+        };
+
+        self.verify_message(bundle.try_into()?, input_digest, policy, options)
+            .await
+    }
+
+    /// Verifies the message signature in `materials` over `input_digest`.
+    async fn verify_message<P>(
+        &self,
+        materials: CheckedBundle,
+        input_digest: MessageDigest,
+        policy: &P,
+        options: &VerificationOptions,
+    ) -> VerificationResult
+    where
+        P: VerificationPolicy,
+    {
+        let SignedContent::MessageSignature {
+            digest_algorithm, ..
+        } = materials.content
+        else {
+            return Err(BundleErrorKind::DsseUnexpected)?;
+        };
+        if digest_algorithm != input_digest.algorithm {
+            return Err(BundleErrorKind::DigestAlgorithmMismatch {
+                bundle: format!("{digest_algorithm:?}"),
+                input: format!("{:?}", input_digest.algorithm),
+            })?;
+        }
+
+        self.verify_materials(&materials, Some(&input_digest.digest), policy, options)
+            .await
+    }
+
+    /// Verifies a Sigstore Bundle containing a DSSE envelope, ensuring conformance to the provided
+    /// [`VerificationPolicy`].
+    ///
+    /// On success, returns the in-toto [`Statement`] carried by the envelope. Callers are
+    /// responsible for matching its subjects against the artifacts they expect.
+    pub async fn verify_dsse<P>(
+        &self,
+        bundle: Bundle,
+        policy: &P,
+        options: &VerificationOptions,
+    ) -> Result<Statement, VerificationError>
     where
         P: VerificationPolicy,
     {
-        let input_digest = input_digest.finalize();
         let materials: CheckedBundle = bundle.try_into()?;
+        let SignedContent::DsseEnvelope(envelope) = &materials.content else {
+            return Err(BundleErrorKind::MessageSignatureUnexpected)?;
+        };
 
+        self.verify_materials(&materials, None, policy, options)
+            .await?;
+
+        if envelope.payload_type != intoto::PAYLOAD_TYPE {
+            return Err(BundleErrorKind::DssePayloadTypeUnsupported(
+                envelope.payload_type.clone(),
+            ))?;
+        }
+        let statement: Statement = serde_json::from_slice(&envelope.payload)
+            .map_err(BundleErrorKind::StatementMalformed)?;
+        if statement.type_ != intoto::STATEMENT_TYPE_V1
+            && statement.type_ != intoto::STATEMENT_TYPE_V0_1
+        {
+            return Err(BundleErrorKind::StatementTypeUnsupported(statement.type_))?;
+        }
+
+        debug!("envelope carries an in-toto statement");
+        Ok(statement)
+    }
+
+    /// Verifies the signing materials of a bundle. `input_digest` is the digest of the signed
+    /// artifact for message signatures, and `None` for DSSE envelopes.
+    async fn verify_materials<P>(
+        &self,
+        materials: &CheckedBundle,
+        input_digest: Option<&[u8]>,
+        policy: &P,
+        options: &VerificationOptions,
+    ) -> VerificationResult
+    where
+        P: VerificationPolicy,
+    {
         // In order to verify an artifact, we need to achieve the following:
         //
         // 1) Verify that the signing certificate is signed by the certificate
         //    chain and that the signing certificate was valid at the time
-        //    of signing.
+        //    of signing, and that it carries a valid SCT.
         // 2) Verify that the signing certificate belongs to the signer.
         // 3) Verify that the artifact signature was signed by the public key in the
         //    signing certificate.
         // 4) Verify that the Rekor entry is consistent with the other signing
         //    materials (preventing CVE-2022-36056)
         // 5) Verify the inclusion proof supplied by Rekor for this artifact,
-        //    if we're doing online verification.
+        //    if it is present (or required by the options), along with its
+        //    signed checkpoint.
         // 6) Verify the Signed Entry Timestamp (SET) supplied by Rekor for this
         //    artifact.
         // 7) Verify that the signing certificate was valid at the time of
         //    signing by comparing the expiry against the integrated timestamp.
+        //
+        // 8) Verify the RFC 3161 signed timestamps over the signature, if any, and
+        //    that the signing certificate was valid at the times they attest to.
+        //
+        // Steps 4 through 7 are repeated for every transparency log entry in the
+        // bundle, which must hold at least as many as the options' threshold. Bundles
+        // without any entry must carry signed timestamps instead.
+        //
+        // Bundles signed with a public key instead of a certificate must be verified
+        // with a policy holding that key: steps 1, 2 and 7, and the validity check of
+        // step 8, do not apply to them.
 
+        // 1-2) Verify the signing certificate and extract its public key. Bundles
+        //      signed with a public key carry no certificate: their signature is
+        //      verified with the key of the policy instead.
+        let (signing_key, verifier) = match (&materials.material, policy.public_key()) {
+            (SigningMaterial::Certificate(certificate), None) => {
+                let signing_key = self.verify_certificate(certificate, policy)?;
+                let pem_certificate = certificate
+                    .to_pem(pkcs8::LineEnding::LF)
+                    .expect("failed to PEM-encode constructed Certificate!");
+                (Cow::Owned(signing_key), pem_certificate)
+            }
+            (SigningMaterial::PublicKey, Some(public_key)) => {
+                debug!("bundle is signed with the policy's public key");
+                (
+                    Cow::Borrowed(public_key.verification_key()),
+                    public_key.pem().to_owned(),
+                )
+            }
+            (SigningMaterial::Certificate(_), Some(_)) => {
+                return Err(PolicyError::CertificateUnexpected)?
+            }
+            (SigningMaterial::PublicKey, None) => return Err(PolicyError::PublicKeyUnexpected)?,
+        };
+
+        // 3) Verify that the signature was signed by the public key in the signing certificate
+        //    (or by the policy's key).
+        let verify_sig = match (&materials.content, input_digest) {
+            (SignedContent::MessageSignature { signature, .. }, Some(input_digest)) => {
+                signing_key.verify_prehash(Signature::Raw(signature), input_digest)
+            }
+            (SignedContent::DsseEnvelope(envelope), None) => signing_key.verify_signature(
+                Signature::Raw(&envelope.signatures[0].sig),
+                &intoto::pae(&envelope.payload_type, &envelope.payload),
+            ),
+            _ => unreachable!("signed content and input digest are checked by the callers"),
+        };
+        verify_sig.map_err(SignatureErrorKind::VerificationFailed)?;
+
+        debug!("signature corresponds to public key");
+
+        // 4-7) Verify each of the bundle's transparency log entries. Each entry must be distinct,
+        //      so that repeating one cannot meet the threshold.
+        let tlog_entries = materials.tlog_entries();
+        let mut seen = HashSet::new();
+        for entry in tlog_entries {
+            let log_id = entry.log_id.as_ref().map(|log_id| &log_id.key_id[..]);
+            if !seen.insert((log_id, entry.log_index)) {
+                return Err(TransparencyErrorKind::DuplicateEntry {
+                    log_id: hex::encode(log_id.unwrap_or_default()),
+                    log_index: entry.log_index,
+                })?;
+            }
+        }
+        if tlog_entries.len() < options.tlog_threshold {
+            return Err(TransparencyErrorKind::EntriesBelowThreshold {
+                threshold: options.tlog_threshold,
+                entries: tlog_entries.len(),
+            })?;
+        }
+        for log_entry in tlog_entries {
+            self.verify_tlog_entry(materials, log_entry, input_digest, &verifier, options)
+                .await?;
+        }
+
+        // 8) Verify the RFC 3161 signed timestamps over the signature, if any, and
+        //    that the signing certificate was valid at the times they attest to.
+        for timestamp in materials.timestamps() {
+            let time = verify_timestamp(timestamp, materials.signature(), &self.tsa_authorities)
+                .map_err(TimestampErrorKind::VerificationFailed)?;
+            if let SigningMaterial::Certificate(certificate) = &materials.material {
+                let time = time.timestamp() as u64;
+                let validity = &certificate.tbs_certificate.validity;
+                if time < validity.not_before.to_unix_duration().as_secs()
+                    || time > validity.not_after.to_unix_duration().as_secs()
+                {
+                    return Err(TimestampErrorKind::OutsideValidityPeriod)?;
+                }
+            }
+            debug!("signed timestamp verified");
+        }
+
+        debug!("successfully verified!");
+        Ok(())
+    }
+
+    /// Verifies that the signing certificate of a bundle chains back to the trust root, that
+    /// it carries a valid SCT and that it conforms to `policy`, returning its public key.
+    fn verify_certificate<P>(
+        &self,
+        certificate: &Certificate,
+        policy: &P,
+    ) -> Result<CosignVerificationKey, VerificationError>
+    where
+        P: VerificationPolicy,
+    {
         // 1) Verify that the signing certificate is signed by the certificate
         //    chain and that the signing certificate was valid at the time
-        //    of signing.
-        let tbs_certificate = &materials.certificate.tbs_certificate;
+        //    of signing, and that it carries a valid SCT.
+        let tbs_certificate = &certificate.tbs_certificate;
         let issued_at = tbs_certificate.validity.not_before.to_unix_duration();
-        let cert_der: CertificateDer = materials
-            .certificate
+        let cert_der: CertificateDer = certificate
             .to_der()
             .expect("failed to DER-encode constructed Certificate!")
             .into();
@@ -117,60 +393,133 @@ impl Verifier {
 
         debug!("signing certificate chains back to trusted root");
 
-        // TODO(tnytown): verify SCT here, sigstore-rs#326
+        verify_embedded_sct(certificate, &self.fulcio_certs, &self.ctfe_keyring)
+            .map_err(CertificateErrorKind::Sct)?;
+        debug!("signing certificate's embedded SCT verified");
 
         // 2) Verify that the signing certificate belongs to the signer.
-        policy.verify(&materials.certificate)?;
+        policy.verify(certificate)?;
         debug!("signing certificate conforms to policy");
 
-        // 3) Verify that the signature was signed by the public key in the signing certificate
-        let signing_key: CosignVerificationKey = (&tbs_certificate.subject_public_key_info)
+        let signing_key = (&tbs_certificate.subject_public_key_info)
             .try_into()
             .map_err(SignatureErrorKind::AlgoUnsupported)?;
 
-        let verify_sig =
-            signing_key.verify_prehash(Signature::Raw(&materials.signature), &input_digest);
-        verify_sig.map_err(SignatureErrorKind::VerificationFailed)?;
+        Ok(signing_key)
+    }
 
-        debug!("signature corresponds to public key");
+    /// Verifies a single transparency log entry of a bundle against its signing materials.
+    /// `verifier` is the PEM-encoded certificate or public key the entry must hold.
+    async fn verify_tlog_entry(
+        &self,
+        materials: &CheckedBundle,
+        log_entry: &TransparencyLogEntry,
+        input_digest: Option<&[u8]>,
+        verifier: &str,
+        options: &VerificationOptions,
+    ) -> VerificationResult {
+        // Bundles may only carry an inclusion promise for their entry. When online, fetch a
+        // fresh inclusion proof from the log instead.
+        let inclusion_proof = match &log_entry.inclusion_proof {
+            Some(inclusion_proof) => Some(Cow::Borrowed(inclusion_proof)),
+            None if !options.offline => {
+                let inclusion_proof = self.fetch_inclusion_proof(log_entry).await?;
+                debug!("fetched inclusion proof from the transparency log");
+                Some(Cow::Owned(inclusion_proof))
+            }
+            None => None,
+        };
+        if options.require_inclusion_proof && inclusion_proof.is_none() {
+            return Err(TransparencyErrorKind::InclusionProofMissing)?;
+        }
 
         // 4) Verify that the Rekor entry is consistent with the other signing
         //    materials
-        let log_entry = materials
-            .tlog_entry(offline, &input_digest)
-            .ok_or(SignatureErrorKind::Transparency)?;
+        if !materials.is_consistent(log_entry, input_digest, verifier) {
+            return Err(SignatureErrorKind::Transparency)?;
+        }
         debug!("log entry is consistent with other materials");
 
         // 5) Verify the inclusion proof supplied by Rekor for this artifact,
-        //    if we're doing online verification.
-        // TODO(tnytown): Merkle inclusion; sigstore-rs#285
+        //    if it is present, along with its signed checkpoint.
+        if let Some(inclusion_proof) = &inclusion_proof {
+            verify_inclusion_proof(inclusion_proof, &log_entry.canonicalized_body)?;
+            debug!("log entry is included in the transparency log");
+
+            if let Some(checkpoint) = &inclusion_proof.checkpoint {
+                verify_checkpoint(&self.rekor_keyring, log_entry, inclusion_proof, checkpoint)?;
+                debug!("inclusion proof is committed to by a signed checkpoint");
+            }
+        }
 
         // 6) Verify the Signed Entry Timestamp (SET) supplied by Rekor for this
         //    artifact.
-        // TODO(tnytown) SET verification; sigstore-rs#285
+        if let Some(inclusion_promise) = &log_entry.inclusion_promise {
+            verify_signed_entry_timestamp(&self.rekor_keyring, log_entry, inclusion_promise)?;
+            debug!("signed entry timestamp verified");
+        }
 
         // 7) Verify that the signing certificate was valid at the time of
         //    signing by comparing the expiry against the integrated timestamp.
-        let integrated_time = log_entry.integrated_time as u64;
-        let not_before = tbs_certificate
-            .validity
-            .not_before
-            .to_unix_duration()
-            .as_secs();
-        let not_after = tbs_certificate
-            .validity
-            .not_after
-            .to_unix_duration()
-            .as_secs();
-        if integrated_time < not_before || integrated_time > not_after {
-            return Err(CertificateErrorKind::Expired)?;
+        if let SigningMaterial::Certificate(certificate) = &materials.material {
+            let validity = &certificate.tbs_certificate.validity;
+            let integrated_time = log_entry.integrated_time as u64;
+            let not_before = validity.not_before.to_unix_duration().as_secs();
+            let not_after = validity.not_after.to_unix_duration().as_secs();
+            if integrated_time < not_before || integrated_time > not_after {
+                return Err(CertificateErrorKind::Expired)?;
+            }
+            debug!("data signed during validity period");
         }
-        debug!("data signed during validity period");
 
-        debug!("successfully verified!");
         Ok(())
     }
 
+    /// Fetches `entry` from the transparency log and returns its current inclusion proof.
+    ///
+    /// The fetched entry must match the bundle's: this proves that the log actually integrated
+    /// the entry it promised to.
+    async fn fetch_inclusion_proof(
+        &self,
+        entry: &TransparencyLogEntry,
+    ) -> Result<InclusionProof, TransparencyErrorKind> {
+        let log_index = entry
+            .log_index
+            .try_into()
+            .or(Err(TransparencyErrorKind::LogEntryMalformed))?;
+        let fetched: TransparencyLogEntry = self
+            .rekor
+            .entry_by_index(log_index)
+            .await
+            .map_err(|err| TransparencyErrorKind::LogEntryFetch(err.to_string()))?
+            .try_into()
+            .or(Err(TransparencyErrorKind::LogEntryMalformed))?;
+
+        // Both bodies are canonical JSON, but they may have been canonicalized by different
+        // implementations: compare them structurally.
+        let body = |entry: &TransparencyLogEntry| {
+            serde_json::from_slice::<serde_json::Value>(&entry.canonicalized_body).ok()
+        };
+        let expected_body = body(entry).ok_or(TransparencyErrorKind::LogEntryMalformed)?;
+        if body(&fetched) != Some(expected_body)
+            || fetched.log_index != entry.log_index
+            || fetched.log_id != entry.log_id
+            || fetched.integrated_time != entry.integrated_time
+        {
+            return Err(TransparencyErrorKind::LogEntryMismatch);
+        }
+
+        // Fetched proofs are only trusted when a signed checkpoint commits to them. Rekor serves
+        // older entries with an empty checkpoint.
+        let inclusion_proof = fetched
+            .inclusion_proof
+            .ok_or(TransparencyErrorKind::InclusionProofMissing)?;
+        match &inclusion_proof.checkpoint {
+            Some(checkpoint) if !checkpoint.envelope.is_empty() => Ok(inclusion_proof),
+            _ => Err(TransparencyErrorKind::CheckpointMissing),
+        }
+    }
+
     /// Verifies an input against the given Sigstore Bundle, ensuring conformance to the provided
     /// [`VerificationPolicy`].
     pub async fn verify<R, P>(
@@ -178,16 +527,18 @@ impl Verifier {
         mut input: R,
         bundle: Bundle,
         policy: &P,
-        offline: bool,
+        options: &VerificationOptions,
     ) -> VerificationResult
     where
         R: AsyncRead + Unpin + Send,
         P: VerificationPolicy,
     {
+        // The input is hashed with the bundle's digest algorithm, so parse it first.
+        let materials: CheckedBundle = bundle.try_into()?;
+        let mut hasher = MessageHasher::from_algorithm(materials.digest_algorithm());
+
         // arbitrary buffer size, chosen to be a multiple of the digest size.
         let mut buf = [0u8; 1024];
-        let mut hasher = Sha256::new();
-
         loop {
             match input
                 .read(&mut buf)
@@ -195,14 +546,107 @@ impl Verifier {
                 .map_err(VerificationError::Input)?
             {
                 0 => break,
-                n => hasher.update(&buf[..n]),
+                n => hasher
+                    .write_all(&buf[..n])
+                    .map_err(VerificationError::Input)?,
             }
         }
 
-        self.verify_digest(hasher, bundle, policy, offline).await
+        self.verify_message(materials, hasher.finalize(), policy, options)
+            .await
     }
 }
 
+/// Verifies that the log entry `body` is committed to by the Merkle tree root in `proof`.
+fn verify_inclusion_proof(
+    proof: &InclusionProof,
+    body: &[u8],
+) -> Result<(), TransparencyErrorKind> {
+    let (Ok(index), Ok(tree_size)) = (proof.log_index.try_into(), proof.tree_size.try_into())
+    else {
+        return Err(TransparencyErrorKind::InclusionProofMalformed);
+    };
+
+    merkle::verify_inclusion(
+        index,
+        tree_size,
+        &merkle::hash_leaf(body),
+        &proof.hashes,
+        &proof.root_hash,
+    )
+    .map_err(TransparencyErrorKind::InclusionProof)
+}
+
+/// Verifies that `checkpoint` is signed by the log that produced `entry` and that it commits to
+/// the same tree as the inclusion `proof`.
+fn verify_checkpoint(
+    keyring: &Keyring,
+    entry: &TransparencyLogEntry,
+    proof: &InclusionProof,
+    checkpoint: &Checkpoint,
+) -> Result<(), TransparencyErrorKind> {
+    let log_id = entry
+        .log_id
+        .as_ref()
+        .ok_or(TransparencyErrorKind::LogIdMissing)?;
+    let tree_size = proof
+        .tree_size
+        .try_into()
+        .or(Err(TransparencyErrorKind::InclusionProofMalformed))?;
+
+    // The log key must have been valid when the entry was integrated.
+    let integrated_time = chrono::DateTime::from_timestamp(entry.integrated_time, 0)
+        .ok_or(TransparencyErrorKind::InclusionProofMalformed)?;
+
+    let checkpoint: SignedCheckpoint = checkpoint
+        .envelope
+        .parse()
+        .map_err(TransparencyErrorKind::Checkpoint)?;
+    checkpoint
+        .verify_signature_at(keyring, &log_id.key_id, integrated_time)
+        .and_then(|_| checkpoint.is_valid_for_proof(&proof.root_hash, tree_size))
+        .map_err(TransparencyErrorKind::Checkpoint)
+}
+
+/// Verifies the Signed Entry Timestamp (SET) of `entry`: Rekor's signature over the canonical JSON
+/// form of the entry's body, integrated time, log index and log ID.
+fn verify_signed_entry_timestamp(
+    keyring: &Keyring,
+    entry: &TransparencyLogEntry,
+    promise: &InclusionPromise,
+) -> Result<(), TransparencyErrorKind> {
+    let log_id = entry
+        .log_id
+        .as_ref()
+        .ok_or(TransparencyErrorKind::LogIdMissing)?;
+
+    let payload = json!({
+        "body": base64.encode(&entry.canonicalized_body),
+        "integratedTime": entry.integrated_time,
+        "logIndex": entry.log_index,
+        "logID": hex::encode(&log_id.key_id),
+    });
+    let payload = {
+        let mut payload = json_syntax::to_value(payload)
+            .or(Err(TransparencyErrorKind::SignedEntryTimestampPayload))?;
+        payload.canonicalize();
+        payload.compact_print().to_string().into_bytes()
+    };
+
+    // The log key must have been valid when the entry was integrated.
+    let integrated_time = chrono::DateTime::from_timestamp(entry.integrated_time, 0)
+        .ok_or(TransparencyErrorKind::SignedEntryTimestampPayload)?;
+
+    keyring
+        .verify_at(
+            &log_id.key_id,
+            &promise.signed_entry_timestamp,
+            &payload,
+            integrated_time,
+        )
+        .map_err(TransparencyErrorKind::SignedEntryTimestamp)
+}
+
 impl Verifier {
     /// Constructs an [`Verifier`] against the public-good trust root.
     #[cfg(feature = "sigstore-trust-root")]
@@ -240,22 +684,38 @@ pub mod blocking {
 
         /// Verifies an input digest against the given Sigstore Bundle, ensuring conformance to the
         /// provided [`VerificationPolicy`].
-        pub fn verify_digest<P>(
+        pub fn verify_digest<D, P>(
             &self,
-            input_digest: Sha256,
+            input_digest: D,
             bundle: Bundle,
             policy: &P,
-            offline: bool,
+            options: &VerificationOptions,
         ) -> VerificationResult
         where
+            D: Digest + AssociatedOid,
             P: VerificationPolicy,
         {
             self.rt.block_on(
                 self.inner
-                    .verify_digest(input_digest, bundle, policy, offline),
+                    .verify_digest(input_digest, bundle, policy, options),
             )
         }
 
+        /// Verifies a Sigstore Bundle containing a DSSE envelope, ensuring conformance to the
+        /// provided [`VerificationPolicy`], and returns the in-toto [`Statement`] it carries.
+        pub fn verify_dsse<P>(
+            &self,
+            bundle: Bundle,
+            policy: &P,
+            options: &VerificationOptions,
+        ) -> Result<Statement, VerificationError>
+        where
+            P: VerificationPolicy,
+        {
+            self.rt
+                .block_on(self.inner.verify_dsse(bundle, policy, options))
+        }
+
         /// Verifies an input against the given Sigstore Bundle, ensuring conformance to the provided
         /// [`VerificationPolicy`].
         pub fn verify<R, P>(
@@ -263,16 +723,22 @@ pub mod blocking {
             mut input: R,
             bundle: Bundle,
             policy: &P,
-            offline: bool,
+            options: &VerificationOptions,
         ) -> VerificationResult
         where
             R: Read,
             P: VerificationPolicy,
         {
-            let mut hasher = Sha256::new();
+            let materials: CheckedBundle = bundle.try_into()?;
+            let mut hasher = MessageHasher::from_algorithm(materials.digest_algorithm());
             io::copy(&mut input, &mut hasher).map_err(VerificationError::Input)?;
 
-            self.verify_digest(hasher, bundle, policy, offline)
+            self.rt.block_on(self.inner.verify_message(
+                materials,
+                hasher.finalize(),
+                policy,
+                options,
+            ))
         }
     }
 
@@ -289,3 +755,455 @@ pub mod blocking {
         }
     }
 }
+
+#[cfg(all(test, feature = "sign"))]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use sha2::{Sha256, Sha384};
+    use url::Url;
+
+    use super::*;
+    use crate::{
+        bundle::{
+            intoto::Subject,
+            sign::{SigningContext, TimestampAuthorityClient},
+            verify::policy,
+        },
+        crypto::{timestamp::tests::FakeTimestampAuthority, SigStoreSigner, SigningScheme},
+        fulcio::{oauth::OauthTokenProvider, FulcioClient, TokenProvider, FULCIO_ROOT},
+        rekor::{
+            apis::configuration::Configuration as RekorConfiguration,
+            client::tests::{serve, FakeRekor},
+        },
+        trust::ManualTrustRoot,
+    };
+
+    pub(crate) fn signing_context(rekor: &FakeRekor) -> SigningContext {
+        let fulcio = FulcioClient::new(
+            Url::parse(FULCIO_ROOT).expect("failed to parse Fulcio URL"),
+            TokenProvider::Oauth(OauthTokenProvider::default()),
+        );
+
+        SigningContext::new(fulcio, rekor.configuration())
+    }
+
+    pub(crate) fn verifier(rekor: &FakeRekor) -> Verifier {
+        let trust_root = ManualTrustRoot {
+            rekor_key: Some(rekor.public_key().to_vec()),
+            ..Default::default()
+        };
+
+        Verifier::new(rekor.configuration(), trust_root).expect("failed to create verifier")
+    }
+
+    /// Creates a key signer for `signing_scheme`, along with a policy holding its public key.
+    pub(crate) fn key_signer(signing_scheme: SigningScheme) -> (SigStoreSigner, policy::PublicKey) {
+        let signer = signing_scheme
+            .create_signer()
+            .expect("failed to create signer");
+        let public_key = signer
+            .to_sigstore_keypair()
+            .and_then(|key_pair| key_pair.public_key_to_der())
+            .expect("failed to encode public key");
+        let policy =
+            policy::PublicKey::new(&public_key, &signing_scheme).expect("failed to create policy");
+
+        (signer, policy)
+    }
+
+    fn statement(type_: &str) -> Statement {
+        Statement {
+            type_: type_.to_owned(),
+            subject: vec![Subject {
+                name: "artifact".to_owned(),
+                digest: BTreeMap::from([("sha256".to_owned(), hex::encode(Sha256::digest(b"")))]),
+            }],
+            predicate_type: "https://slsa.dev/provenance/v1".to_owned(),
+            predicate: None,
+        }
+    }
+
+    async fn verify_dsse(
+        payload_type: &str,
+        payload: Vec<u8>,
+    ) -> Result<Statement, VerificationError> {
+        let rekor = FakeRekor::new();
+        let context = signing_context(&rekor);
+        let (signer, policy) = key_signer(SigningScheme::ECDSA_P256_SHA256_ASN1);
+        let bundle = context
+            .key_signer(signer)
+            .expect("failed to create session")
+            .sign_dsse(payload_type, payload)
+            .await
+            .expect("failed to sign")
+            .to_bundle();
+
+        verifier(&rekor)
+            .verify_dsse(bundle, &policy, &VerificationOptions::default())
+            .await
+    }
+
+    #[rstest::rstest]
+    #[case(intoto::STATEMENT_TYPE_V1)]
+    #[case(intoto::STATEMENT_TYPE_V0_1)]
+    #[tokio::test]
+    async fn verify_dsse_statement(#[case] type_: &str) {
+        let statement = statement(type_);
+        let payload = serde_json::to_vec(&statement).expect("failed to serialize statement");
+
+        let verified = verify_dsse(intoto::PAYLOAD_TYPE, payload)
+            .await
+            .expect("failed to verify");
+        assert_eq!(verified, statement);
+    }
+
+    #[tokio::test]
+    async fn verify_dsse_rejects_payload_type() {
+        let payload = serde_json::to_vec(&statement(intoto::STATEMENT_TYPE_V1))
+            .expect("failed to serialize statement");
+
+        let err = verify_dsse("application/json", payload).await.unwrap_err();
+        assert!(matches!(
+            err,
+            VerificationError::Bundle(BundleErrorKind::DssePayloadTypeUnsupported(payload_type))
+                if payload_type == "application/json"
+        ));
+    }
+
+    #[tokio::test]
+    async fn verify_dsse_rejects_statement_type() {
+        let payload = serde_json::to_vec(&statement("https://in-toto.io/Statement/v2"))
+            .expect("failed to serialize statement");
+
+        let err = verify_dsse(intoto::PAYLOAD_TYPE, payload)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            VerificationError::Bundle(BundleErrorKind::StatementTypeUnsupported(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn verify_dsse_rejects_malformed_statement() {
+        let err = verify_dsse(intoto::PAYLOAD_TYPE, b"{}".to_vec())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            VerificationError::Bundle(BundleErrorKind::StatementMalformed(_))
+        ));
+    }
+
+    async fn sign_message(
+        rekor: &FakeRekor,
+        signing_scheme: SigningScheme,
+        input: &'static [u8],
+    ) -> (policy::PublicKey, Bundle) {
+        let (signer, policy) = key_signer(signing_scheme);
+        let bundle = signing_context(rekor)
+            .key_signer(signer)
+            .expect("failed to create session")
+            .sign(input)
+            .await
+            .expect("failed to sign")
+            .to_bundle();
+
+        (policy, bundle)
+    }
+
+    #[rstest::rstest]
+    #[case(SigningScheme::ECDSA_P256_SHA256_ASN1)]
+    #[case(SigningScheme::ECDSA_P384_SHA384_ASN1)]
+    #[case(SigningScheme::RSA_PKCS1_SHA512(2048))]
+    #[tokio::test]
+    async fn verify_message_signature(#[case] signing_scheme: SigningScheme) {
+        let rekor = FakeRekor::new();
+        let (policy, bundle) = sign_message(&rekor, signing_scheme, b"hello").await;
+
+        verifier(&rekor)
+            .verify(
+                &b"hello"[..],
+                bundle,
+                &policy,
+                &VerificationOptions::default(),
+            )
+            .await
+            .expect("failed to verify");
+    }
+
+    #[tokio::test]
+    async fn verify_digest_p384() {
+        let rekor = FakeRekor::new();
+        let verifier = verifier(&rekor);
+        let options = VerificationOptions::default();
+        let (policy, bundle) =
+            sign_message(&rekor, SigningScheme::ECDSA_P384_SHA384_ASN1, b"hello").await;
+
+        verifier
+            .verify_digest(
+                Sha384::new_with_prefix(b"hello"),
+                bundle.clone(),
+                &policy,
+                &options,
+            )
+            .await
+            .expect("failed to verify");
+
+        let err = verifier
+            .verify_digest(Sha256::new_with_prefix(b"hello"), bundle, &policy, &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            VerificationError::Bundle(BundleErrorKind::DigestAlgorithmMismatch { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn verify_rejects_duplicate_entries() {
+        let rekor = FakeRekor::new();
+        let (policy, mut bundle) =
+            sign_message(&rekor, SigningScheme::ECDSA_P256_SHA256_ASN1, b"hello").await;
+        let material = bundle
+            .verification_material
+            .as_mut()
+            .expect("bundle has no verification material");
+        material.tlog_entries.push(material.tlog_entries[0].clone());
+
+        let options = VerificationOptions {
+            tlog_threshold: 2,
+            ..Default::default()
+        };
+        let err = verifier(&rekor)
+            .verify(&b"hello"[..], bundle, &policy, &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            VerificationError::Transparency(TransparencyErrorKind::DuplicateEntry { .. })
+        ));
+    }
+
+    /// Signs `input` with a fresh key, returning the policy holding the key and a v0.1 bundle
+    /// that only carries the inclusion promise of its log entry.
+    async fn promise_only_bundle(
+        context: &SigningContext,
+        input: &'static [u8],
+    ) -> (policy::PublicKey, Bundle) {
+        let (signer, policy) = key_signer(SigningScheme::ECDSA_P256_SHA256_ASN1);
+        let mut bundle = context
+            .key_signer(signer)
+            .expect("failed to create session")
+            .sign(input)
+            .await
+            .expect("failed to sign")
+            .to_versioned_bundle(crate::bundle::Version::Bundle0_1);
+        for entry in &mut bundle
+            .verification_material
+            .as_mut()
+            .expect("bundle has no verification material")
+            .tlog_entries
+        {
+            entry.inclusion_proof = None;
+        }
+
+        (policy, bundle)
+    }
+
+    #[tokio::test]
+    async fn verify_promise_only_entry() {
+        let rekor = FakeRekor::new();
+        let (policy, bundle) = promise_only_bundle(&signing_context(&rekor), b"artifact").await;
+        let verifier = verifier(&rekor);
+
+        let options = VerificationOptions::default();
+        assert!(options.offline);
+        verifier
+            .verify(&b"artifact"[..], bundle.clone(), &policy, &options)
+            .await
+            .expect("failed to verify offline");
+
+        let options = VerificationOptions {
+            require_inclusion_proof: true,
+            ..Default::default()
+        };
+        let err = verifier
+            .verify(&b"artifact"[..], bundle.clone(), &policy, &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            VerificationError::Transparency(TransparencyErrorKind::InclusionProofMissing)
+        ));
+
+        let options = VerificationOptions {
+            offline: false,
+            require_inclusion_proof: true,
+            ..Default::default()
+        };
+        verifier
+            .verify(&b"artifact"[..], bundle, &policy, &options)
+            .await
+            .expect("failed to verify with a fetched inclusion proof");
+    }
+
+    #[tokio::test]
+    async fn fetch_inclusion_proof_rejects_other_entry() {
+        let rekor = FakeRekor::new();
+        let context = signing_context(&rekor);
+        let (policy, mut bundle) = promise_only_bundle(&context, b"artifact").await;
+        let (_, other) = promise_only_bundle(&context, b"other artifact").await;
+
+        // Point the bundle's entry at the other entry's position in the log.
+        let other_index = other
+            .verification_material
+            .expect("bundle has no verification material")
+            .tlog_entries[0]
+            .log_index;
+        bundle
+            .verification_material
+            .as_mut()
+            .expect("bundle has no verification material")
+            .tlog_entries[0]
+            .log_index = other_index;
+
+        let options = VerificationOptions {
+            offline: false,
+            ..Default::default()
+        };
+        let err = verifier(&rekor)
+            .verify(&b"artifact"[..], bundle, &policy, &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            VerificationError::Transparency(TransparencyErrorKind::LogEntryMismatch)
+        ));
+    }
+
+    #[tokio::test]
+    async fn fetch_inclusion_proof_requires_checkpoint() {
+        let rekor = FakeRekor::new();
+        let (policy, bundle) = promise_only_bundle(&signing_context(&rekor), b"artifact").await;
+
+        // A log serving the entry with an empty checkpoint, like older entries.
+        let mut response: serde_json::Value = reqwest::get(format!(
+            "{}/api/v1/log/entries?logIndex=0",
+            rekor.configuration().base_path
+        ))
+        .await
+        .and_then(|response| response.error_for_status())
+        .expect("failed to fetch entry")
+        .json()
+        .await
+        .expect("failed to read entry");
+        for entry in response
+            .as_object_mut()
+            .into_iter()
+            .flat_map(|e| e.values_mut())
+        {
+            entry["verification"]["inclusionProof"]["checkpoint"] = "".into();
+        }
+        let response = response.to_string();
+        let url = serve(move |_| (200, response.clone()));
+        let trust_root = ManualTrustRoot {
+            rekor_key: Some(rekor.public_key().to_vec()),
+            ..Default::default()
+        };
+        let verifier = Verifier::new(
+            RekorConfiguration {
+                base_path: url,
+                ..Default::default()
+            },
+            trust_root,
+        )
+        .expect("failed to create verifier");
+
+        let options = VerificationOptions {
+            offline: false,
+            ..Default::default()
+        };
+        let err = verifier
+            .verify(&b"artifact"[..], bundle, &policy, &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            VerificationError::Transparency(TransparencyErrorKind::CheckpointMissing)
+        ));
+    }
+
+    #[tokio::test]
+    async fn verify_timestamp_only_bundle() {
+        let rekor = FakeRekor::new();
+        let tsa = FakeTimestampAuthority::new();
+        let (signer, _) = key_signer(SigningScheme::ECDSA_P256_SHA256_ASN1);
+
+        // Signatures that are not logged must be timestamped.
+        let context = signing_context(&rekor).without_transparency_log();
+        let session = context
+            .key_signer(signer)
+            .expect("failed to create session");
+        assert!(session.sign(&b"artifact"[..]).await.is_err());
+
+        let (signer, policy) = key_signer(SigningScheme::ECDSA_P256_SHA256_ASN1);
+        let context = signing_context(&rekor)
+            .without_transparency_log()
+            .with_timestamp_authority(TimestampAuthorityClient::new(tsa.url()));
+        let bundle = context
+            .key_signer(signer)
+            .expect("failed to create session")
+            .sign(&b"artifact"[..])
+            .await
+            .expect("failed to sign")
+            .to_bundle();
+        let material = bundle.verification_material.as_ref().unwrap();
+        assert!(material.tlog_entries.is_empty());
+        assert!(material.timestamp_verification_data.is_some());
+
+        let trust_root = ManualTrustRoot {
+            rekor_key: Some(rekor.public_key().to_vec()),
+            tsa_certs: tsa.certs(),
+            ..Default::default()
+        };
+        let verifier =
+            Verifier::new(rekor.configuration(), trust_root).expect("failed to create verifier");
+
+        let err = verifier
+            .verify(
+                &b"artifact"[..],
+                bundle.clone(),
+                &policy,
+                &VerificationOptions::default(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            VerificationError::Transparency(TransparencyErrorKind::EntriesBelowThreshold {
+                threshold: 1,
+                entries: 0
+            })
+        ));
+
+        let options = VerificationOptions {
+            tlog_threshold: 0,
+            ..Default::default()
+        };
+        verifier
+            .verify(&b"artifact"[..], bundle.clone(), &policy, &options)
+            .await
+            .expect("failed to verify timestamp-only bundle");
+
+        let err = verifier
+            .verify(&b"other artifact"[..], bundle, &policy, &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            VerificationError::Signature(SignatureErrorKind::VerificationFailed(_))
+        ));
+    }
+}
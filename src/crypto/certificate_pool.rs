@@ -103,18 +103,31 @@ impl CertificatePool {
         cert: &'cert EndEntityCert<'cert>,
         verification_time: UnixTime,
     ) -> Result<VerifiedPath<'cert>, webpki::Error>
+    where
+        'a: 'cert,
+    {
+        self.verify_cert_for_usage(cert, verification_time, ID_KP_CODE_SIGNING.as_bytes())
+    }
+
+    /// Ensures the given certificate has been issued by one of the trusted root certificates,
+    /// and that it carries the extended key usage `eku`.
+    pub(crate) fn verify_cert_for_usage<'a, 'cert>(
+        &'a self,
+        cert: &'cert EndEntityCert<'cert>,
+        verification_time: UnixTime,
+        eku: &'static [u8],
+    ) -> Result<VerifiedPath<'cert>, webpki::Error>
     where
         'a: 'cert,
     {
         let signing_algs = webpki::ALL_VERIFICATION_ALGS;
-        let eku_code_signing = ID_KP_CODE_SIGNING.as_bytes();
 
         cert.verify_for_usage(
             signing_algs,
             &self.trusted_roots,
             self.intermediates.as_slice(),
             verification_time,
-            KeyUsage::required(eku_code_signing),
+            KeyUsage::required(eku),
             None,
             None,
         )
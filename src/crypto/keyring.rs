@@ -0,0 +1,201 @@
+//
+// Copyright 2024 The Sigstore Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A collection of transparency log keys, indexed by log ID.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use super::{CosignVerificationKey, Signature};
+use crate::errors::{Result as SigstoreResult, SigstoreError};
+use crate::trust::{TransparencyLogKey, ValidityPeriod};
+
+#[derive(Error, Debug)]
+pub enum KeyringError {
+    #[error("no key found for log ID {0}")]
+    KeyNotFound(String),
+
+    #[error("key for log ID {0} is not valid at {1}")]
+    KeyNotValid(String, DateTime<Utc>),
+
+    #[error("signature verification failed")]
+    VerificationFailed(#[source] SigstoreError),
+}
+
+/// A set of public keys, each addressed by its key ID and trusted for a period of time.
+///
+/// Both Rekor and CT logs identify their signing key with a log ID, defined as the SHA-256 digest
+/// of the DER-encoded `SubjectPublicKeyInfo` of the key.
+#[derive(Debug, Default)]
+pub struct Keyring(HashMap<Vec<u8>, (CosignVerificationKey, ValidityPeriod)>);
+
+impl Keyring {
+    /// Builds a [`Keyring`] from DER-encoded `SubjectPublicKeyInfo` keys, trusted at any time.
+    pub fn new<'a, I>(keys: I) -> SigstoreResult<Self>
+    where
+        I: IntoIterator<Item = &'a [u8]>,
+    {
+        Self::from_log_keys(keys.into_iter().map(|der| TransparencyLogKey {
+            log_id: key_id(der).to_vec(),
+            hash_algorithm: crate::trust::HashAlgorithm::Sha2_256,
+            public_key: der,
+            valid_for: ValidityPeriod::default(),
+        }))
+    }
+
+    /// Builds a [`Keyring`] from transparency log keys, addressed by their log ID and trusted
+    /// for their validity period.
+    pub fn from_log_keys<'a, I>(keys: I) -> SigstoreResult<Self>
+    where
+        I: IntoIterator<Item = TransparencyLogKey<'a>>,
+    {
+        let keys = keys
+            .into_iter()
+            .map(|key| {
+                let public_key = CosignVerificationKey::try_from_der(key.public_key)?;
+                Ok((key.log_id, (public_key, key.valid_for)))
+            })
+            .collect::<SigstoreResult<_>>()?;
+
+        Ok(Self(keys))
+    }
+
+    /// Returns `true` if the keyring does not hold any keys.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Verifies `signature` over `data` with the key identified by `key_id`, regardless of its
+    /// validity period.
+    pub fn verify(&self, key_id: &[u8], signature: &[u8], data: &[u8]) -> Result<(), KeyringError> {
+        let (key, _) = self.get(key_id)?;
+
+        key.verify_signature(Signature::Raw(signature), data)
+            .map_err(KeyringError::VerificationFailed)
+    }
+
+    /// Verifies `signature` over `data` with the key identified by `key_id`, which must have been
+    /// valid at `time`.
+    pub fn verify_at(
+        &self,
+        key_id: &[u8],
+        signature: &[u8],
+        data: &[u8],
+        time: DateTime<Utc>,
+    ) -> Result<(), KeyringError> {
+        let (key, valid_for) = self.get(key_id)?;
+        if !valid_for.contains(time) {
+            return Err(KeyringError::KeyNotValid(hex::encode(key_id), time));
+        }
+
+        key.verify_signature(Signature::Raw(signature), data)
+            .map_err(KeyringError::VerificationFailed)
+    }
+
+    fn get(&self, key_id: &[u8]) -> Result<&(CosignVerificationKey, ValidityPeriod), KeyringError> {
+        self.0
+            .get(key_id)
+            .ok_or_else(|| KeyringError::KeyNotFound(hex::encode(key_id)))
+    }
+}
+
+/// Computes the key ID of a DER-encoded `SubjectPublicKeyInfo`.
+pub fn key_id(der: &[u8]) -> [u8; 32] {
+    Sha256::digest(der).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::SigningScheme;
+
+    fn keypair() -> (crate::crypto::SigStoreSigner, Vec<u8>) {
+        let signer = SigningScheme::ECDSA_P256_SHA256_ASN1
+            .create_signer()
+            .expect("failed to create signer");
+        let public_key = signer
+            .to_sigstore_keypair()
+            .and_then(|k| k.public_key_to_der())
+            .expect("failed to export public key");
+
+        (signer, public_key)
+    }
+
+    #[test]
+    fn verify_known_key() {
+        let (signer, public_key) = keypair();
+        let keyring = Keyring::new([public_key.as_slice()]).expect("failed to build keyring");
+
+        let signature = signer.sign(b"payload").expect("failed to sign");
+        assert!(keyring
+            .verify(&key_id(&public_key), &signature, b"payload")
+            .is_ok());
+        assert!(matches!(
+            keyring.verify(&key_id(&public_key), &signature, b"tampered"),
+            Err(KeyringError::VerificationFailed(_))
+        ));
+    }
+
+    #[test]
+    fn verify_unknown_key() {
+        let (signer, public_key) = keypair();
+        let (_, other_public_key) = keypair();
+        let keyring = Keyring::new([public_key.as_slice()]).expect("failed to build keyring");
+
+        let signature = signer.sign(b"payload").expect("failed to sign");
+        assert!(matches!(
+            keyring.verify(&key_id(&other_public_key), &signature, b"payload"),
+            Err(KeyringError::KeyNotFound(_))
+        ));
+        assert!(matches!(
+            keyring.verify(b"short", &signature, b"payload"),
+            Err(KeyringError::KeyNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn verify_key_validity() {
+        let (signer, public_key) = keypair();
+        let at = |secs| DateTime::from_timestamp(secs, 0).expect("invalid timestamp");
+        let keyring = Keyring::from_log_keys([TransparencyLogKey {
+            log_id: b"log".to_vec(),
+            hash_algorithm: crate::trust::HashAlgorithm::Sha2_256,
+            public_key: &public_key,
+            valid_for: ValidityPeriod {
+                start: Some(at(100)),
+                end: Some(at(200)),
+            },
+        }])
+        .expect("failed to build keyring");
+
+        let signature = signer.sign(b"payload").expect("failed to sign");
+        assert!(keyring
+            .verify_at(b"log", &signature, b"payload", at(150))
+            .is_ok());
+        assert!(matches!(
+            keyring.verify_at(b"log", &signature, b"payload", at(250)),
+            Err(KeyringError::KeyNotValid(..))
+        ));
+        assert!(keyring.verify(b"log", &signature, b"payload").is_ok());
+    }
+
+    #[test]
+    fn malformed_key() {
+        assert!(Keyring::new([b"not a key".as_slice()]).is_err());
+    }
+}
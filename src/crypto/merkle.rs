@@ -0,0 +1,465 @@
+//
+// Copyright 2024 The Sigstore Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Merkle tree hashing and proof verification, as specified by
+//! [RFC 6962](https://www.rfc-editor.org/rfc/rfc6962#section-2.1).
+//!
+//! The proof verification algorithms follow the reference implementation found in
+//! [transparency-dev/merkle](https://github.com/transparency-dev/merkle/blob/main/proof/verify.go).
+
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// The output of the RFC 6962 hash function (SHA-256).
+pub type MerkleHash = [u8; 32];
+
+const LEAF_HASH_PREFIX: u8 = 0x00;
+const NODE_HASH_PREFIX: u8 = 0x01;
+
+#[derive(Error, Debug)]
+pub enum MerkleProofError {
+    #[error("index {index} is out of range for a tree of size {tree_size}")]
+    IndexOutOfRange { index: u64, tree_size: u64 },
+
+    #[error("wrong proof size: got {got}, expected {expected}")]
+    WrongProofSize { got: usize, expected: usize },
+
+    #[error("proof contains a hash of length {0}, expected 32")]
+    MalformedHash(usize),
+
+    #[error("tree size {size1} is larger than tree size {size2}")]
+    TreeSizesOutOfOrder { size1: u64, size2: u64 },
+
+    #[error("calculated root {calculated} does not match expected root {expected}")]
+    RootMismatch {
+        calculated: String,
+        expected: String,
+    },
+}
+
+/// Computes the hash of a leaf: `SHA-256(0x00 || leaf)`.
+pub fn hash_leaf(leaf: &[u8]) -> MerkleHash {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_HASH_PREFIX]);
+    hasher.update(leaf);
+    hasher.finalize().into()
+}
+
+/// Computes the hash of an interior node: `SHA-256(0x01 || left || right)`.
+pub fn hash_children(left: &[u8], right: &[u8]) -> MerkleHash {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_HASH_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Verifies that `leaf_hash` is included at position `index` of a tree of size `tree_size`
+/// with the given `root`, using the audit path `proof` (ordered from leaf to root).
+pub fn verify_inclusion<H: AsRef<[u8]>>(
+    index: u64,
+    tree_size: u64,
+    leaf_hash: &MerkleHash,
+    proof: &[H],
+    root: &[u8],
+) -> Result<(), MerkleProofError> {
+    let calculated = root_from_inclusion_proof(index, tree_size, leaf_hash, proof)?;
+    verify_match(&calculated, root)
+}
+
+/// Computes the root implied by an inclusion proof for `leaf_hash` at position `index`.
+pub fn root_from_inclusion_proof<H: AsRef<[u8]>>(
+    index: u64,
+    tree_size: u64,
+    leaf_hash: &MerkleHash,
+    proof: &[H],
+) -> Result<MerkleHash, MerkleProofError> {
+    if index >= tree_size {
+        return Err(MerkleProofError::IndexOutOfRange { index, tree_size });
+    }
+    let proof = to_hashes(proof)?;
+
+    let (inner, border) = decompose_inclusion_proof(index, tree_size);
+    if proof.len() != inner + border {
+        return Err(MerkleProofError::WrongProofSize {
+            got: proof.len(),
+            expected: inner + border,
+        });
+    }
+
+    let res = chain_inner(*leaf_hash, &proof[..inner], index);
+    Ok(chain_border_right(res, &proof[inner..]))
+}
+
+/// Verifies that the tree of size `size2` with root `root2` is an append-only extension of the
+/// tree of size `size1` with root `root1`, using the consistency `proof` between them.
+pub fn verify_consistency<H: AsRef<[u8]>>(
+    size1: u64,
+    size2: u64,
+    proof: &[H],
+    root1: &[u8],
+    root2: &[u8],
+) -> Result<(), MerkleProofError> {
+    let proof = to_hashes(proof)?;
+    let wrong_size = |expected| MerkleProofError::WrongProofSize {
+        got: proof.len(),
+        expected,
+    };
+
+    if size2 < size1 {
+        return Err(MerkleProofError::TreeSizesOutOfOrder { size1, size2 });
+    } else if size1 == size2 {
+        if !proof.is_empty() {
+            return Err(wrong_size(0));
+        }
+        return verify_match(&to_hash(root1)?, root2);
+    } else if size1 == 0 {
+        // The empty tree is consistent with every tree.
+        if !proof.is_empty() {
+            return Err(wrong_size(0));
+        }
+        return Ok(());
+    } else if proof.is_empty() {
+        return Err(wrong_size(1));
+    }
+
+    let (inner, border) = decompose_inclusion_proof(size1 - 1, size2);
+    let shift = size1.trailing_zeros() as usize;
+    let inner = inner - shift;
+
+    // The proof starts with the root of the largest perfect subtree of the first tree, unless
+    // the first tree is itself perfect.
+    let (seed, proof) = if size1 == 1 << shift {
+        (to_hash(root1)?, &proof[..])
+    } else {
+        (proof[0], &proof[1..])
+    };
+    if proof.len() != inner + border {
+        return Err(wrong_size(
+            inner + border + usize::from(size1 != 1 << shift),
+        ));
+    }
+
+    let mask = (size1 - 1) >> shift;
+    let hash1 = chain_inner_right(seed, &proof[..inner], mask);
+    let hash1 = chain_border_right(hash1, &proof[inner..]);
+    verify_match(&hash1, root1)?;
+
+    let hash2 = chain_inner(seed, &proof[..inner], mask);
+    let hash2 = chain_border_right(hash2, &proof[inner..]);
+    verify_match(&hash2, root2)
+}
+
+fn to_hash(h: &[u8]) -> Result<MerkleHash, MerkleProofError> {
+    h.try_into()
+        .map_err(|_| MerkleProofError::MalformedHash(h.len()))
+}
+
+fn to_hashes<H: AsRef<[u8]>>(proof: &[H]) -> Result<Vec<MerkleHash>, MerkleProofError> {
+    proof.iter().map(|h| to_hash(h.as_ref())).collect()
+}
+
+fn verify_match(calculated: &MerkleHash, expected: &[u8]) -> Result<(), MerkleProofError> {
+    if calculated[..] != expected[..] {
+        return Err(MerkleProofError::RootMismatch {
+            calculated: hex::encode(calculated),
+            expected: hex::encode(expected),
+        });
+    }
+
+    Ok(())
+}
+
+/// Breaks an inclusion proof for `index` in a tree of `size` down into the number of hashes
+/// belonging to the "inner" (shared with the perfect subtree) and "border" parts of the path.
+fn decompose_inclusion_proof(index: u64, size: u64) -> (usize, usize) {
+    let inner = inner_proof_size(index, size);
+    let border = (index >> inner).count_ones() as usize;
+    (inner, border)
+}
+
+fn inner_proof_size(index: u64, size: u64) -> usize {
+    (u64::BITS - (index ^ (size - 1)).leading_zeros()) as usize
+}
+
+fn chain_inner(mut seed: MerkleHash, proof: &[MerkleHash], index: u64) -> MerkleHash {
+    for (i, h) in proof.iter().enumerate() {
+        seed = if (index >> i) & 1 == 0 {
+            hash_children(&seed, h)
+        } else {
+            hash_children(h, &seed)
+        };
+    }
+    seed
+}
+
+fn chain_inner_right(mut seed: MerkleHash, proof: &[MerkleHash], index: u64) -> MerkleHash {
+    for (i, h) in proof.iter().enumerate() {
+        if (index >> i) & 1 == 1 {
+            seed = hash_children(h, &seed);
+        }
+    }
+    seed
+}
+
+fn chain_border_right(mut seed: MerkleHash, proof: &[MerkleHash]) -> MerkleHash {
+    for h in proof {
+        seed = hash_children(h, &seed);
+    }
+    seed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reference Merkle Tree Hash (MTH), straight from RFC 6962 section 2.1.
+    fn mth(leaves: &[Vec<u8>]) -> MerkleHash {
+        match leaves.len() {
+            0 => Sha256::digest([]).into(),
+            1 => hash_leaf(&leaves[0]),
+            n => {
+                let k = split_point(n);
+                hash_children(&mth(&leaves[..k]), &mth(&leaves[k..]))
+            }
+        }
+    }
+
+    /// Reference audit path (PATH), straight from RFC 6962 section 2.1.1.
+    fn path(m: usize, leaves: &[Vec<u8>]) -> Vec<MerkleHash> {
+        let n = leaves.len();
+        if n <= 1 {
+            return vec![];
+        }
+
+        let k = split_point(n);
+        if m < k {
+            let mut p = path(m, &leaves[..k]);
+            p.push(mth(&leaves[k..]));
+            p
+        } else {
+            let mut p = path(m - k, &leaves[k..]);
+            p.push(mth(&leaves[..k]));
+            p
+        }
+    }
+
+    /// The largest power of two smaller than `n`.
+    fn split_point(n: usize) -> usize {
+        let mut k = 1;
+        while k << 1 < n {
+            k <<= 1;
+        }
+        k
+    }
+
+    /// Reference consistency proof (PROOF), straight from RFC 6962 section 2.1.2.
+    fn proof(m: usize, leaves: &[Vec<u8>]) -> Vec<MerkleHash> {
+        fn subproof(m: usize, leaves: &[Vec<u8>], complete: bool) -> Vec<MerkleHash> {
+            let n = leaves.len();
+            if m == n {
+                return if complete { vec![] } else { vec![mth(leaves)] };
+            }
+
+            let k = split_point(n);
+            if m <= k {
+                let mut p = subproof(m, &leaves[..k], complete);
+                p.push(mth(&leaves[k..]));
+                p
+            } else {
+                let mut p = subproof(m - k, &leaves[k..], false);
+                p.push(mth(&leaves[..k]));
+                p
+            }
+        }
+
+        subproof(m, leaves, true)
+    }
+
+    fn leaves(n: usize) -> Vec<Vec<u8>> {
+        (0..n).map(|i| format!("leaf {i}").into_bytes()).collect()
+    }
+
+    #[test]
+    fn inclusion_proofs_verify() {
+        for size in 1..=32 {
+            let leaves = leaves(size);
+            let root = mth(&leaves);
+
+            for index in 0..size {
+                let proof = path(index, &leaves);
+                verify_inclusion(
+                    index as u64,
+                    size as u64,
+                    &hash_leaf(&leaves[index]),
+                    &proof,
+                    &root,
+                )
+                .unwrap_or_else(|e| panic!("index {index} in tree of size {size}: {e}"));
+            }
+        }
+    }
+
+    #[test]
+    fn inclusion_proof_wrong_leaf() {
+        let leaves = leaves(7);
+        let root = mth(&leaves);
+        let proof = path(3, &leaves);
+
+        assert!(matches!(
+            verify_inclusion(3, 7, &hash_leaf(b"not a leaf"), &proof, &root),
+            Err(MerkleProofError::RootMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn inclusion_proof_tampered() {
+        let leaves = leaves(7);
+        let root = mth(&leaves);
+        let mut proof = path(3, &leaves);
+        proof[1][0] ^= 1;
+
+        assert!(matches!(
+            verify_inclusion(3, 7, &hash_leaf(&leaves[3]), &proof, &root),
+            Err(MerkleProofError::RootMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn inclusion_proof_wrong_index() {
+        let leaves = leaves(7);
+        let root = mth(&leaves);
+        let proof = path(3, &leaves);
+
+        assert!(verify_inclusion(2, 7, &hash_leaf(&leaves[3]), &proof, &root).is_err());
+        assert!(matches!(
+            verify_inclusion(7, 7, &hash_leaf(&leaves[3]), &proof, &root),
+            Err(MerkleProofError::IndexOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn inclusion_proof_wrong_size() {
+        let leaves = leaves(7);
+        let root = mth(&leaves);
+        let mut proof = path(3, &leaves);
+        proof.push(root);
+
+        assert!(matches!(
+            verify_inclusion(3, 7, &hash_leaf(&leaves[3]), &proof, &root),
+            Err(MerkleProofError::WrongProofSize { .. })
+        ));
+    }
+
+    #[test]
+    fn inclusion_proof_malformed_hash() {
+        let leaves = leaves(2);
+        let root = mth(&leaves);
+
+        assert!(matches!(
+            verify_inclusion(0, 2, &hash_leaf(&leaves[0]), &[vec![0u8; 31]], &root),
+            Err(MerkleProofError::MalformedHash(31))
+        ));
+    }
+
+    #[test]
+    fn consistency_proofs_verify() {
+        for size2 in 1..=32 {
+            let leaves = leaves(size2);
+            let root2 = mth(&leaves);
+
+            for size1 in 1..=size2 {
+                let root1 = mth(&leaves[..size1]);
+                verify_consistency(
+                    size1 as u64,
+                    size2 as u64,
+                    &proof(size1, &leaves),
+                    &root1,
+                    &root2,
+                )
+                .unwrap_or_else(|e| panic!("trees of sizes {size1} and {size2}: {e}"));
+            }
+        }
+    }
+
+    #[test]
+    fn consistency_proof_empty_tree() {
+        let root = mth(&leaves(3));
+        let no_proof: &[MerkleHash] = &[];
+
+        assert!(verify_consistency(0, 3, no_proof, &[], &root).is_ok());
+        assert!(matches!(
+            verify_consistency(0, 3, &[root], &[], &root),
+            Err(MerkleProofError::WrongProofSize { .. })
+        ));
+    }
+
+    #[test]
+    fn consistency_proof_same_size() {
+        let root = mth(&leaves(5));
+        let no_proof: &[MerkleHash] = &[];
+
+        assert!(verify_consistency(5, 5, no_proof, &root, &root).is_ok());
+        assert!(matches!(
+            verify_consistency(5, 5, no_proof, &root, &mth(&leaves(4))),
+            Err(MerkleProofError::RootMismatch { .. })
+        ));
+        assert!(matches!(
+            verify_consistency(5, 5, &[root], &root, &root),
+            Err(MerkleProofError::WrongProofSize { .. })
+        ));
+    }
+
+    #[test]
+    fn consistency_proof_rewritten_log() {
+        let leaves = leaves(7);
+        let mut rewritten = leaves.clone();
+        rewritten[1] = b"rewritten".to_vec();
+
+        // The first tree was rewritten: its root no longer matches the proof.
+        assert!(matches!(
+            verify_consistency(
+                3,
+                7,
+                &proof(3, &rewritten),
+                &mth(&leaves[..3]),
+                &mth(&rewritten)
+            ),
+            Err(MerkleProofError::RootMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn consistency_proof_tampered() {
+        let leaves = leaves(7);
+        let (root1, root2) = (mth(&leaves[..3]), mth(&leaves));
+        let mut proof = proof(3, &leaves);
+        proof[1][0] ^= 1;
+
+        assert!(matches!(
+            verify_consistency(3, 7, &proof, &root1, &root2),
+            Err(MerkleProofError::RootMismatch { .. })
+        ));
+        assert!(matches!(
+            verify_consistency(7, 3, &proof, &root2, &root1),
+            Err(MerkleProofError::TreeSizesOutOfOrder { .. })
+        ));
+        proof.pop();
+        assert!(matches!(
+            verify_consistency(3, 7, &proof, &root1, &root2),
+            Err(MerkleProofError::WrongProofSize { .. })
+        ));
+    }
+}
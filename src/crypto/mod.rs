@@ -178,6 +178,12 @@ pub(crate) mod certificate_pool;
 #[cfg(feature = "cert")]
 pub(crate) use certificate_pool::CertificatePool;
 
+pub mod keyring;
+pub mod merkle;
+#[cfg(feature = "cert")]
+pub mod timestamp;
+#[cfg(feature = "cert")]
+pub mod transparency;
 pub mod verification_key;
 
 use self::signing_key::{
@@ -87,7 +87,7 @@ use elliptic_curve::{
     AffinePoint, Curve, CurveArithmetic, FieldBytesSize, PublicKey, Scalar, SecretKey,
 };
 use pkcs8::{AssociatedOid, DecodePrivateKey, EncodePrivateKey, EncodePublicKey};
-use signature::DigestSigner;
+use signature::{hazmat::PrehashSigner, DigestSigner};
 
 use crate::{
     crypto::{
@@ -331,7 +331,8 @@ impl<C, D> Signer for EcdsaSigner<C, D>
 where
     C: PrimeCurve + CurveArithmetic + AssociatedOid + DigestPrimitive,
     Scalar<C>: Invert<Output = CtOption<Scalar<C>>> + Reduce<C::Uint> + SignPrimitive<C>,
-    SigningKey<C>: ecdsa::signature::Signer<ecdsa::Signature<C>>,
+    SigningKey<C>:
+        ecdsa::signature::Signer<ecdsa::Signature<C>> + PrehashSigner<ecdsa::Signature<C>>,
     C::Uint: for<'a> From<&'a Scalar<C>>,
     <<C as Curve>::FieldBytesSize as Add>::Output:
         Add<UInt<UInt<UInt<UInt<UTerm, B1>, B0>, B0>, B1>>,
@@ -359,6 +360,16 @@ where
         Ok(sig.to_der().to_bytes().to_vec())
     }
 
+    /// Sign the given digest of a message, computed with the
+    /// digest algorithm `D`.
+    ///
+    /// The outcome digest will be encoded in `asn.1`.
+    fn sign_prehash(&self, digest: &[u8]) -> Result<Vec<u8>> {
+        let sig: ecdsa::Signature<C> = self.signing_key.sign_prehash(digest)?;
+
+        Ok(sig.to_der().to_bytes().to_vec())
+    }
+
     /// Return the ref to the keypair inside the signer
     fn key_pair(&self) -> &dyn KeyPair {
         &self.ecdsa_keys
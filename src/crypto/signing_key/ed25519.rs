@@ -276,6 +276,13 @@ impl Signer for Ed25519Signer {
         let signature = self.key_pair.signing_key.try_sign(msg)?;
         Ok(signature.to_vec())
     }
+
+    /// Ed25519 signs messages in full, thus it cannot sign digests.
+    fn sign_prehash(&self, _digest: &[u8]) -> Result<Vec<u8>> {
+        Err(SigstoreError::PrehashSigningUnsupported(
+            "ED25519".to_string(),
+        ))
+    }
 }
 
 #[cfg(test)]
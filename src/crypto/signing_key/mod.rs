@@ -305,6 +305,17 @@ pub trait Signer {
 
     /// `sign` will sign the given data, and return the signature.
     fn sign(&self, msg: &[u8]) -> Result<Vec<u8>>;
+
+    /// `sign_prehash` will sign the given digest of some data, computed
+    /// with the signer's hash function, and return the signature.
+    ///
+    /// Signers that can only sign messages in full do not need to implement it:
+    /// by default, it fails with [`SigstoreError::PrehashSigningUnsupported`].
+    fn sign_prehash(&self, _digest: &[u8]) -> Result<Vec<u8>> {
+        Err(SigstoreError::PrehashSigningUnsupported(
+            std::any::type_name::<Self>().to_string(),
+        ))
+    }
 }
 
 #[derive(Debug)]
@@ -343,9 +354,16 @@ impl SigStoreSigner {
         self.as_inner().sign(msg)
     }
 
-    /// `to_verification_key` will derive the verification_key for the `SigStoreSigner`.
-    pub fn to_verification_key(&self) -> Result<CosignVerificationKey> {
-        let signing_scheme = match self {
+    /// `sign_prehash` will sign the given digest of some data, and return the
+    /// signature. The digest must be computed with the hash function of the
+    /// signer's [`SigningScheme`]. Ed25519 signers cannot sign digests.
+    pub fn sign_prehash(&self, digest: &[u8]) -> Result<Vec<u8>> {
+        self.as_inner().sign_prehash(digest)
+    }
+
+    /// `signing_scheme` will return the [`SigningScheme`] of the `SigStoreSigner`.
+    pub fn signing_scheme(&self) -> SigningScheme {
+        match self {
             SigStoreSigner::ECDSA_P256_SHA256_ASN1(_) => SigningScheme::ECDSA_P256_SHA256_ASN1,
             SigStoreSigner::ECDSA_P384_SHA384_ASN1(_) => SigningScheme::ECDSA_P384_SHA384_ASN1,
             SigStoreSigner::ED25519(_) => SigningScheme::ED25519,
@@ -355,10 +373,14 @@ impl SigStoreSigner {
             SigStoreSigner::RSA_PKCS1_SHA256(_) => SigningScheme::RSA_PKCS1_SHA256(0),
             SigStoreSigner::RSA_PKCS1_SHA384(_) => SigningScheme::RSA_PKCS1_SHA384(0),
             SigStoreSigner::RSA_PKCS1_SHA512(_) => SigningScheme::RSA_PKCS1_SHA512(0),
-        };
+        }
+    }
+
+    /// `to_verification_key` will derive the verification_key for the `SigStoreSigner`.
+    pub fn to_verification_key(&self) -> Result<CosignVerificationKey> {
         self.as_inner()
             .key_pair()
-            .to_verification_key(&signing_scheme)
+            .to_verification_key(&self.signing_scheme())
     }
 
     /// `key_pair` will return the reference of the `SigStoreKeyPair` enum due to `SigStoreSigner`.
@@ -392,6 +414,7 @@ impl SigStoreSigner {
 #[cfg(test)]
 mod tests {
     use rstest::rstest;
+    use sha2::Digest;
 
     use crate::crypto::{verification_key::CosignVerificationKey, Signature, SigningScheme};
 
@@ -442,4 +465,61 @@ mod tests {
         let verify_res = verification_key.verify_signature(signature, MESSAGE.as_bytes());
         assert!(verify_res.is_ok(), "can not verify the signature.");
     }
+
+    /// Signing the digest of the MESSAGE must produce a signature over
+    /// the MESSAGE itself.
+    #[rstest]
+    #[case(SigningScheme::ECDSA_P256_SHA256_ASN1, sha2::Sha256::digest(MESSAGE).to_vec())]
+    #[case(SigningScheme::ECDSA_P384_SHA384_ASN1, sha2::Sha384::digest(MESSAGE).to_vec())]
+    #[case(SigningScheme::RSA_PSS_SHA256(2048), sha2::Sha256::digest(MESSAGE).to_vec())]
+    #[case(SigningScheme::RSA_PKCS1_SHA512(2048), sha2::Sha512::digest(MESSAGE).to_vec())]
+    fn sigstore_signing_prehash(#[case] signing_scheme: SigningScheme, #[case] digest: Vec<u8>) {
+        let signer = signing_scheme
+            .create_signer()
+            .unwrap_or_else(|_| panic!("create SigStoreSigner with {:?} failed", signing_scheme));
+        let sig = signer.sign_prehash(&digest).expect("sign digest failed.");
+        let verification_key = signer
+            .to_verification_key()
+            .expect("derive signer into verification key failed.");
+        let verify_res =
+            verification_key.verify_signature(Signature::Raw(&sig), MESSAGE.as_bytes());
+        assert!(verify_res.is_ok(), "can not verify the signature.");
+    }
+
+    #[test]
+    fn sigstore_signing_prehash_ed25519() {
+        let signer = SigningScheme::ED25519
+            .create_signer()
+            .expect("create SigStoreSigner failed");
+        assert!(signer.sign_prehash(&[0; 64]).is_err());
+    }
+
+    #[test]
+    fn sigstore_signing_prehash_default() {
+        use super::{
+            ed25519::{Ed25519Keys, Ed25519Signer},
+            KeyPair, Signer,
+        };
+        use crate::errors::{Result, SigstoreError};
+
+        /// A signer that only implements signing messages in full.
+        struct MessageSigner(Ed25519Keys);
+
+        impl Signer for MessageSigner {
+            fn key_pair(&self) -> &dyn KeyPair {
+                &self.0
+            }
+
+            fn sign(&self, msg: &[u8]) -> Result<Vec<u8>> {
+                Ed25519Signer::from_ed25519_keys(&self.0)?.sign(msg)
+            }
+        }
+
+        let signer = MessageSigner(Ed25519Keys::new().expect("create Ed25519 keys failed"));
+        assert!(signer.sign(MESSAGE.as_bytes()).is_ok());
+        assert!(matches!(
+            signer.sign_prehash(&[0; 32]),
+            Err(SigstoreError::PrehashSigningUnsupported(_))
+        ));
+    }
 }
@@ -53,7 +53,10 @@
 use ::rsa::{
     pkcs1v15::SigningKey,
     pss::BlindedSigningKey,
-    signature::{Keypair, RandomizedSigner, SignatureEncoding},
+    signature::{
+        hazmat::{PrehashSigner, RandomizedPrehashSigner},
+        Keypair, RandomizedSigner, SignatureEncoding,
+    },
 };
 
 use self::keypair::RSAKeys;
@@ -199,6 +202,26 @@ impl Signer for RSASigner {
         ))
     }
 
+    /// `sign_prehash` will sign the given digest of a message, computed with the
+    /// signer's digest algorithm, and return the signature.
+    fn sign_prehash(&self, digest: &[u8]) -> Result<Vec<u8>> {
+        let mut rng = rand::thread_rng();
+        Ok(match self {
+            RSASigner::RSA_PSS_SHA256(signer, _) => {
+                signer.sign_prehash_with_rng(&mut rng, digest)?.to_vec()
+            }
+            RSASigner::RSA_PSS_SHA384(signer, _) => {
+                signer.sign_prehash_with_rng(&mut rng, digest)?.to_vec()
+            }
+            RSASigner::RSA_PSS_SHA512(signer, _) => {
+                signer.sign_prehash_with_rng(&mut rng, digest)?.to_vec()
+            }
+            RSASigner::RSA_PKCS1_SHA256(signer, _) => signer.sign_prehash(digest)?.to_vec(),
+            RSASigner::RSA_PKCS1_SHA384(signer, _) => signer.sign_prehash(digest)?.to_vec(),
+            RSASigner::RSA_PKCS1_SHA512(signer, _) => signer.sign_prehash(digest)?.to_vec(),
+        })
+    }
+
     /// Return the ref to the [`KeyPair`] trait object inside the RSASigner
     fn key_pair(&self) -> &dyn KeyPair {
         iter_on_rsa!(RSASigner, self, _signer, key, key)
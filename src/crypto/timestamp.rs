@@ -0,0 +1,966 @@
+//
+// Copyright 2024 The Sigstore Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of [RFC 3161] signed timestamps, as issued by timestamp authorities (TSAs).
+//!
+//! [RFC 3161]: https://www.rfc-editor.org/rfc/rfc3161
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use const_oid::db::{
+    rfc5280::{ID_CE_SUBJECT_KEY_IDENTIFIER, ID_KP_TIME_STAMPING},
+    rfc5911::{ID_CONTENT_TYPE, ID_MESSAGE_DIGEST, ID_SIGNED_DATA},
+    rfc5912::{
+        ECDSA_WITH_SHA_256, ECDSA_WITH_SHA_384, ECDSA_WITH_SHA_512, ID_EC_PUBLIC_KEY, ID_SHA_256,
+        ID_SHA_384, ID_SHA_512, RSA_ENCRYPTION, SHA_256_WITH_RSA_ENCRYPTION,
+        SHA_384_WITH_RSA_ENCRYPTION, SHA_512_WITH_RSA_ENCRYPTION,
+    },
+};
+use der::{
+    asn1::{Any, AnyRef, BitString, Int, ObjectIdentifier, OctetString, SetOfVec},
+    Choice, Decode, DecodeValue, Encode, EncodeValue, FixedTag, Header, Length, Reader, Sequence,
+    Tag, Tagged, Writer,
+};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use thiserror::Error;
+use webpki::{
+    types::{CertificateDer, UnixTime},
+    EndEntityCert,
+};
+use x509_cert::{
+    attr::Attribute, name::Name, serial_number::SerialNumber, spki::AlgorithmIdentifierOwned,
+    Certificate,
+};
+
+use super::{CertificatePool, CosignVerificationKey, Signature, SigningScheme};
+use crate::errors::Result as SigstoreResult;
+use crate::trust::{CertificateAuthority, ValidityPeriod};
+
+/// `id-ct-TSTInfo`, the content type of timestamp tokens.
+const ID_CT_TST_INFO: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.1.4");
+
+#[derive(Error, Debug)]
+pub enum TimestampError {
+    #[error("signed timestamp is malformed: {0}")]
+    Malformed(String),
+
+    #[error("timestamp request was rejected with status {0}")]
+    Rejected(u32),
+
+    #[error("timestamp token has unexpected content type {0}")]
+    ContentTypeUnexpected(ObjectIdentifier),
+
+    #[error("timestamp token needs 1 signer, got {0}")]
+    Signers(usize),
+
+    #[error("timestamp token is missing its signed attributes")]
+    SignedAttributesMissing,
+
+    #[error("unsupported algorithm {0}")]
+    AlgorithmUnsupported(ObjectIdentifier),
+
+    #[error("timestamp token's message digest does not match its content")]
+    MessageDigestMismatch,
+
+    #[error("timestamp is not over the expected signature")]
+    MessageImprintMismatch,
+
+    #[error("timestamp authority certificate not found")]
+    CertificateNotFound,
+
+    #[error("timestamp authority certificate verification failed")]
+    CertificateVerificationFailed(#[source] webpki::Error),
+
+    #[error("no timestamp authority was trusted at the time of the timestamp")]
+    OutsideAuthorityValidity,
+
+    #[error("timestamp signature verification failed")]
+    VerificationFailed(#[source] crate::errors::SigstoreError),
+}
+
+fn malformed(err: impl std::fmt::Display) -> TimestampError {
+    TimestampError::Malformed(err.to_string())
+}
+
+/// `TimeStampResp`, from [RFC 3161 § 2.4.2](https://www.rfc-editor.org/rfc/rfc3161#section-2.4.2).
+#[derive(Clone, Debug, Sequence)]
+struct TimeStampResp {
+    status: PkiStatusInfo,
+    #[asn1(optional = "true")]
+    time_stamp_token: Option<ContentInfo>,
+}
+
+#[derive(Clone, Debug, Sequence)]
+struct PkiStatusInfo {
+    status: u32,
+    #[asn1(optional = "true")]
+    status_string: Option<Vec<String>>,
+    #[asn1(optional = "true")]
+    fail_info: Option<BitString>,
+}
+
+/// `ContentInfo`, from [RFC 5652 § 3](https://www.rfc-editor.org/rfc/rfc5652#section-3).
+#[derive(Clone, Debug, Sequence)]
+struct ContentInfo {
+    content_type: ObjectIdentifier,
+    #[asn1(context_specific = "0", tag_mode = "EXPLICIT")]
+    content: Any,
+}
+
+/// `SignedData`, from [RFC 5652 § 5.1](https://www.rfc-editor.org/rfc/rfc5652#section-5.1).
+#[derive(Clone, Debug, Sequence)]
+struct SignedData {
+    version: u8,
+    digest_algorithms: SetOfVec<AlgorithmIdentifierOwned>,
+    encap_content_info: EncapsulatedContentInfo,
+    #[asn1(context_specific = "0", tag_mode = "IMPLICIT", optional = "true")]
+    certificates: Option<SetOfVec<Certificate>>,
+    #[asn1(context_specific = "1", tag_mode = "IMPLICIT", optional = "true")]
+    crls: Option<RawSet>,
+    signer_infos: SetOfVec<SignerInfo>,
+}
+
+#[derive(Clone, Debug, Sequence)]
+struct EncapsulatedContentInfo {
+    e_content_type: ObjectIdentifier,
+    #[asn1(context_specific = "0", tag_mode = "EXPLICIT", optional = "true")]
+    e_content: Option<OctetString>,
+}
+
+/// `SignerInfo`, from [RFC 5652 § 5.3](https://www.rfc-editor.org/rfc/rfc5652#section-5.3).
+#[derive(Clone, Debug, Eq, PartialEq, Sequence, der::ValueOrd)]
+struct SignerInfo {
+    version: u8,
+    sid: SignerIdentifier,
+    digest_algorithm: AlgorithmIdentifierOwned,
+    #[asn1(context_specific = "0", tag_mode = "IMPLICIT", optional = "true")]
+    signed_attrs: Option<RawSet>,
+    signature_algorithm: AlgorithmIdentifierOwned,
+    signature: OctetString,
+    #[asn1(context_specific = "1", tag_mode = "IMPLICIT", optional = "true")]
+    unsigned_attrs: Option<RawSet>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Choice, der::ValueOrd)]
+enum SignerIdentifier {
+    IssuerAndSerialNumber(IssuerAndSerialNumber),
+    #[asn1(context_specific = "0", tag_mode = "IMPLICIT")]
+    SubjectKeyIdentifier(OctetString),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Sequence, der::ValueOrd)]
+struct IssuerAndSerialNumber {
+    issuer: Name,
+    serial_number: SerialNumber,
+}
+
+/// The contents of a `SET OF`, kept verbatim.
+///
+/// Signatures over signed attributes cover their exact encoding, which decoding and re-encoding
+/// them would not preserve for non-DER encoders.
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+struct RawSet(Vec<u8>);
+
+impl RawSet {
+    /// Returns the DER encoding of the set, with its universal `SET` tag.
+    fn to_set_der(&self) -> der::Result<Vec<u8>> {
+        Any::new(Tag::Set, self.0.clone())?.to_der()
+    }
+}
+
+impl<'a> DecodeValue<'a> for RawSet {
+    fn decode_value<R: Reader<'a>>(reader: &mut R, header: Header) -> der::Result<Self> {
+        Ok(Self(reader.read_vec(header.length)?))
+    }
+}
+
+impl EncodeValue for RawSet {
+    fn value_len(&self) -> der::Result<Length> {
+        self.0.len().try_into()
+    }
+
+    fn encode_value(&self, writer: &mut impl Writer) -> der::Result<()> {
+        writer.write(&self.0)
+    }
+}
+
+impl FixedTag for RawSet {
+    const TAG: Tag = Tag::Set;
+}
+
+impl der::ValueOrd for RawSet {
+    fn value_cmp(&self, other: &Self) -> der::Result<std::cmp::Ordering> {
+        Ok(self.cmp(other))
+    }
+}
+
+/// `TSTInfo`, from [RFC 3161 § 2.4.2](https://www.rfc-editor.org/rfc/rfc3161#section-2.4.2).
+///
+/// Only the fields needed for verification are kept.
+#[derive(Clone, Debug)]
+struct TstInfo {
+    message_imprint: MessageImprint,
+    gen_time: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Sequence)]
+struct MessageImprint {
+    hash_algorithm: AlgorithmIdentifierOwned,
+    hashed_message: OctetString,
+}
+
+impl<'a> DecodeValue<'a> for TstInfo {
+    fn decode_value<R: Reader<'a>>(reader: &mut R, header: Header) -> der::Result<Self> {
+        reader.read_nested(header.length, |reader| {
+            let _version: u8 = reader.decode()?;
+            let _policy: ObjectIdentifier = reader.decode()?;
+            let message_imprint = reader.decode()?;
+            let _serial_number: Int = reader.decode()?;
+
+            // `genTime` may carry fractional seconds, which `der::asn1::GeneralizedTime` rejects.
+            let gen_time: AnyRef = reader.decode()?;
+            gen_time.tag().assert_eq(Tag::GeneralizedTime)?;
+            let gen_time = parse_generalized_time(gen_time.value())
+                .ok_or_else(|| Tag::GeneralizedTime.value_error())?;
+
+            // `accuracy`, `ordering`, `nonce`, `tsa` and `extensions` are not used.
+            while !reader.is_finished() {
+                reader.decode::<AnyRef>()?;
+            }
+
+            Ok(Self {
+                message_imprint,
+                gen_time,
+            })
+        })
+    }
+}
+
+impl FixedTag for TstInfo {
+    const TAG: Tag = Tag::Sequence;
+}
+
+/// Parses a `GeneralizedTime` of the form `YYYYMMDDhhmmss[.f*]Z`, truncating fractional seconds.
+fn parse_generalized_time(value: &[u8]) -> Option<DateTime<Utc>> {
+    let value = std::str::from_utf8(value).ok()?.strip_suffix('Z')?;
+    let (seconds, fraction) = value.split_once('.').unwrap_or((value, "0"));
+    if seconds.len() != 14 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    NaiveDateTime::parse_from_str(seconds, "%Y%m%d%H%M%S")
+        .ok()
+        .map(|time| time.and_utc())
+}
+
+/// Hashes `data` with the given digest algorithm.
+fn digest(algorithm: &AlgorithmIdentifierOwned, data: &[u8]) -> Result<Vec<u8>, TimestampError> {
+    match algorithm.oid {
+        ID_SHA_256 => Ok(Sha256::digest(data).to_vec()),
+        ID_SHA_384 => Ok(Sha384::digest(data).to_vec()),
+        ID_SHA_512 => Ok(Sha512::digest(data).to_vec()),
+        oid => Err(TimestampError::AlgorithmUnsupported(oid)),
+    }
+}
+
+/// Returns the [`SigningScheme`] of an RSA CMS signer, given its signature and digest algorithms.
+fn rsa_signing_scheme(
+    signature_algorithm: &AlgorithmIdentifierOwned,
+    digest_algorithm: &AlgorithmIdentifierOwned,
+) -> Result<SigningScheme, TimestampError> {
+    match (signature_algorithm.oid, digest_algorithm.oid) {
+        (SHA_256_WITH_RSA_ENCRYPTION, _) | (RSA_ENCRYPTION, ID_SHA_256) => {
+            Ok(SigningScheme::RSA_PKCS1_SHA256(0))
+        }
+        (SHA_384_WITH_RSA_ENCRYPTION, _) | (RSA_ENCRYPTION, ID_SHA_384) => {
+            Ok(SigningScheme::RSA_PKCS1_SHA384(0))
+        }
+        (SHA_512_WITH_RSA_ENCRYPTION, _) | (RSA_ENCRYPTION, ID_SHA_512) => {
+            Ok(SigningScheme::RSA_PKCS1_SHA512(0))
+        }
+        (oid, _) => Err(TimestampError::AlgorithmUnsupported(oid)),
+    }
+}
+
+/// Verifies the signature of a CMS signer over its DER-encoded signed attributes.
+///
+/// The curve of ECDSA signers is that of their certificate's key: their signature algorithm only
+/// names the digest algorithm.
+fn verify_signer_signature(
+    signer: &Certificate,
+    signer_info: &SignerInfo,
+    signed_attrs: &[u8],
+) -> Result<(), TimestampError> {
+    let signature = Signature::Raw(signer_info.signature.as_bytes());
+    let spki = &signer.tbs_certificate.subject_public_key_info;
+
+    let ecdsa_digest = match signer_info.signature_algorithm.oid {
+        ECDSA_WITH_SHA_256 => Some(Sha256::digest(signed_attrs).to_vec()),
+        ECDSA_WITH_SHA_384 => Some(Sha384::digest(signed_attrs).to_vec()),
+        ECDSA_WITH_SHA_512 => Some(Sha512::digest(signed_attrs).to_vec()),
+        _ => None,
+    };
+    match ecdsa_digest {
+        Some(digest) => {
+            if spki.algorithm.oid != ID_EC_PUBLIC_KEY {
+                return Err(TimestampError::AlgorithmUnsupported(spki.algorithm.oid));
+            }
+            CosignVerificationKey::try_from(spki)
+                .and_then(|key| key.verify_prehash(signature, &digest))
+        }
+        None => {
+            let scheme = rsa_signing_scheme(
+                &signer_info.signature_algorithm,
+                &signer_info.digest_algorithm,
+            )?;
+            let spki = spki.to_der().map_err(malformed)?;
+            CosignVerificationKey::from_der(&spki, &scheme)
+                .and_then(|key| key.verify_signature(signature, signed_attrs))
+        }
+    }
+    .map_err(TimestampError::VerificationFailed)
+}
+
+/// Returns the first value of the signed attribute `oid`.
+fn signed_attribute(attributes: &SetOfVec<Attribute>, oid: ObjectIdentifier) -> Option<&Any> {
+    attributes
+        .iter()
+        .find(|attribute| attribute.oid == oid)
+        .and_then(|attribute| attribute.values.iter().next())
+}
+
+/// Returns whether `cert` is the certificate identified by `sid`.
+fn is_signer(cert: &Certificate, sid: &SignerIdentifier) -> bool {
+    match sid {
+        SignerIdentifier::IssuerAndSerialNumber(id) => {
+            cert.tbs_certificate.issuer == id.issuer
+                && cert.tbs_certificate.serial_number == id.serial_number
+        }
+        SignerIdentifier::SubjectKeyIdentifier(ski) => cert
+            .tbs_certificate
+            .extensions
+            .iter()
+            .flatten()
+            .filter(|ext| ext.extn_id == ID_CE_SUBJECT_KEY_IDENTIFIER)
+            .filter_map(|ext| OctetString::from_der(ext.extn_value.as_bytes()).ok())
+            .any(|value| &value == ski),
+    }
+}
+
+/// The timestamp authorities trusted to issue signed timestamps.
+pub struct TimestampAuthorities {
+    authorities: Vec<(CertificatePool, ValidityPeriod)>,
+    certs: Vec<Certificate>,
+}
+
+impl TimestampAuthorities {
+    /// Builds a [`TimestampAuthorities`] from the certificate chains of the trusted authorities,
+    /// trusted to issue timestamps at any time.
+    pub fn new<'a, I>(certs: I) -> SigstoreResult<Self>
+    where
+        I: IntoIterator<Item = CertificateDer<'a>>,
+    {
+        Self::from_authorities([CertificateAuthority {
+            cert_chain: certs.into_iter().collect(),
+            valid_for: ValidityPeriod::default(),
+        }])
+    }
+
+    /// Builds a [`TimestampAuthorities`] from the trusted authorities, each of which is only
+    /// trusted for the timestamps it issues within its validity period.
+    pub fn from_authorities<'a, I>(authorities: I) -> SigstoreResult<Self>
+    where
+        I: IntoIterator<Item = CertificateAuthority<'a>>,
+    {
+        let mut trusted = Vec::new();
+        let mut certs = Vec::new();
+        for authority in authorities {
+            if authority.cert_chain.is_empty() {
+                continue;
+            }
+
+            let pool =
+                CertificatePool::from_certificates(authority.cert_chain.iter().cloned(), [])?;
+            for cert in &authority.cert_chain {
+                certs.push(Certificate::from_der(cert)?);
+            }
+            trusted.push((pool, authority.valid_for));
+        }
+
+        Ok(Self {
+            authorities: trusted,
+            certs,
+        })
+    }
+
+    /// Returns `true` if no timestamp authority is trusted.
+    pub fn is_empty(&self) -> bool {
+        self.certs.is_empty()
+    }
+}
+
+/// `TimeStampReq`, from [RFC 3161 § 2.4.1](https://www.rfc-editor.org/rfc/rfc3161#section-2.4.1).
+#[derive(Clone, Debug, Sequence)]
+struct TimeStampReq {
+    version: u8,
+    message_imprint: MessageImprint,
+    #[asn1(optional = "true")]
+    req_policy: Option<ObjectIdentifier>,
+    #[asn1(optional = "true")]
+    nonce: Option<u64>,
+    cert_req: bool,
+}
+
+/// A decoded timestamp token.
+struct Token {
+    signed_data: SignedData,
+    /// The DER-encoded `TSTInfo`, as signed.
+    content: Vec<u8>,
+    tst_info: TstInfo,
+}
+
+impl Token {
+    /// Decodes a DER-encoded `TimeStampResp`, or a bare `TimeStampToken`.
+    fn decode(timestamp: &[u8]) -> Result<Self, TimestampError> {
+        let token = match TimeStampResp::from_der(timestamp) {
+            // 0 is `granted`, 1 is `grantedWithMods`.
+            Ok(response) if response.status.status > 1 => {
+                return Err(TimestampError::Rejected(response.status.status))
+            }
+            Ok(response) => response
+                .time_stamp_token
+                .ok_or(TimestampError::Malformed("response has no token".into()))?,
+            Err(_) => ContentInfo::from_der(timestamp).map_err(malformed)?,
+        };
+
+        if token.content_type != ID_SIGNED_DATA {
+            return Err(TimestampError::ContentTypeUnexpected(token.content_type));
+        }
+        let signed_data: SignedData = token.content.decode_as().map_err(malformed)?;
+
+        let encap_content_info = &signed_data.encap_content_info;
+        if encap_content_info.e_content_type != ID_CT_TST_INFO {
+            return Err(TimestampError::ContentTypeUnexpected(
+                encap_content_info.e_content_type,
+            ));
+        }
+        let content = encap_content_info
+            .e_content
+            .as_ref()
+            .ok_or(TimestampError::Malformed("token has no content".into()))?
+            .as_bytes()
+            .to_vec();
+        let tst_info = TstInfo::from_der(&content).map_err(malformed)?;
+
+        Ok(Self {
+            signed_data,
+            content,
+            tst_info,
+        })
+    }
+
+    /// Checks that the token timestamps `signature`.
+    fn check_imprint(&self, signature: &[u8]) -> Result<(), TimestampError> {
+        let imprint = &self.tst_info.message_imprint;
+        if imprint.hashed_message.as_bytes() != digest(&imprint.hash_algorithm, signature)? {
+            return Err(TimestampError::MessageImprintMismatch);
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds a DER-encoded `TimeStampReq` for a timestamp over `signature`.
+///
+/// The request asks for the timestamp authority's certificate to be included in the token.
+pub(crate) fn timestamp_request(signature: &[u8]) -> Result<Vec<u8>, TimestampError> {
+    TimeStampReq {
+        version: 1,
+        message_imprint: MessageImprint {
+            hash_algorithm: AlgorithmIdentifierOwned {
+                oid: ID_SHA_256,
+                parameters: None,
+            },
+            hashed_message: OctetString::new(Sha256::digest(signature).to_vec())
+                .map_err(malformed)?,
+        },
+        req_policy: None,
+        nonce: None,
+        cert_req: true,
+    }
+    .to_der()
+    .map_err(malformed)
+}
+
+/// Checks that a `TimeStampResp` returned by a timestamp authority grants a timestamp over
+/// `signature`, without verifying its signer.
+pub(crate) fn check_timestamp_response(
+    response: &[u8],
+    signature: &[u8],
+) -> Result<(), TimestampError> {
+    Token::decode(response)?.check_imprint(signature)
+}
+
+/// Verifies an RFC 3161 signed timestamp over `signature`, returning the time it attests to.
+///
+/// `timestamp` is a DER-encoded `TimeStampResp`, or a bare `TimeStampToken`. The token must be
+/// signed by one of the `authorities`, which must have been trusted at that time, and whose
+/// certificate must have been valid then.
+pub fn verify_timestamp(
+    timestamp: &[u8],
+    signature: &[u8],
+    authorities: &TimestampAuthorities,
+) -> Result<DateTime<Utc>, TimestampError> {
+    // The token must timestamp the expected signature.
+    let token = Token::decode(timestamp)?;
+    token.check_imprint(signature)?;
+    let Token {
+        signed_data,
+        content,
+        tst_info,
+    } = token;
+
+    let [signer_info] = signed_data.signer_infos.as_slice() else {
+        return Err(TimestampError::Signers(signed_data.signer_infos.len()));
+    };
+
+    // The signed attributes must commit to the token's content.
+    let signed_attrs = signer_info
+        .signed_attrs
+        .as_ref()
+        .ok_or(TimestampError::SignedAttributesMissing)?
+        .to_set_der()
+        .map_err(malformed)?;
+    let attributes = SetOfVec::<Attribute>::from_der(&signed_attrs).map_err(malformed)?;
+    let content_type = signed_attribute(&attributes, ID_CONTENT_TYPE)
+        .ok_or(TimestampError::SignedAttributesMissing)?
+        .decode_as::<ObjectIdentifier>()
+        .map_err(malformed)?;
+    if content_type != ID_CT_TST_INFO {
+        return Err(TimestampError::ContentTypeUnexpected(content_type));
+    }
+    let message_digest = signed_attribute(&attributes, ID_MESSAGE_DIGEST)
+        .ok_or(TimestampError::SignedAttributesMissing)?
+        .decode_as::<OctetString>()
+        .map_err(malformed)?;
+    if message_digest.as_bytes() != digest(&signer_info.digest_algorithm, &content)? {
+        return Err(TimestampError::MessageDigestMismatch);
+    }
+
+    // The signer must be a trusted timestamp authority at the time of the timestamp.
+    let signer = signed_data
+        .certificates
+        .iter()
+        .flat_map(|certs| certs.iter())
+        .chain(&authorities.certs)
+        .find(|cert| is_signer(cert, &signer_info.sid))
+        .ok_or(TimestampError::CertificateNotFound)?;
+    let signer_der = CertificateDer::from(signer.to_der().map_err(malformed)?);
+    let ee_cert = EndEntityCert::try_from(&signer_der)
+        .map_err(TimestampError::CertificateVerificationFailed)?;
+    let gen_time = tst_info
+        .gen_time
+        .timestamp()
+        .try_into()
+        .map_err(|_| TimestampError::Malformed("timestamp predates the epoch".into()))?;
+    let mut verified = Err(TimestampError::OutsideAuthorityValidity);
+    for (pool, valid_for) in &authorities.authorities {
+        if !valid_for.contains(tst_info.gen_time) {
+            continue;
+        }
+
+        verified = pool
+            .verify_cert_for_usage(
+                &ee_cert,
+                UnixTime::since_unix_epoch(std::time::Duration::from_secs(gen_time)),
+                ID_KP_TIME_STAMPING.as_bytes(),
+            )
+            .map(|_| ())
+            .map_err(TimestampError::CertificateVerificationFailed);
+        if verified.is_ok() {
+            break;
+        }
+    }
+    verified?;
+
+    verify_signer_signature(signer, signer_info, &signed_attrs)?;
+
+    Ok(tst_info.gen_time)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use der::asn1::GeneralizedTime;
+    use openssl::{
+        hash::MessageDigest,
+        pkey::{PKey, Private, Public},
+        sign::Signer,
+        x509::{
+            extension::{BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectKeyIdentifier},
+            X509NameBuilder, X509,
+        },
+    };
+    use x509_cert::attr::AttributeValue;
+
+    use super::*;
+    use crate::crypto::tests::{
+        generate_certificate, generate_ecdsa_p256_keypair, generate_ecdsa_p384_keypair, CertData,
+    };
+
+    /// A `TSTInfo`, with only its mandatory fields.
+    #[derive(Sequence)]
+    struct TestTstInfo {
+        version: u8,
+        policy: ObjectIdentifier,
+        message_imprint: MessageImprint,
+        serial_number: u32,
+        gen_time: GeneralizedTime,
+    }
+
+    struct Fixture {
+        root: CertData,
+        tsa: X509,
+        tsa_key: PKey<Private>,
+    }
+
+    fn fixture() -> Fixture {
+        fixture_with_key(generate_ecdsa_p384_keypair())
+    }
+
+    /// Creates a fixture whose timestamp authority holds the given key pair. Timestamps are
+    /// signed with ECDSA and SHA-384, whatever the curve of the key.
+    fn fixture_with_key((tsa_key, tsa_public_key): (PKey<Private>, PKey<Public>)) -> Fixture {
+        let root = generate_certificate(None, Default::default()).expect("failed to generate root");
+        let tsa = {
+            let mut name = X509NameBuilder::new().unwrap();
+            name.append_entry_by_text("CN", "tsa.sigstore.test")
+                .unwrap();
+            let name = name.build();
+
+            let mut builder = X509::builder().unwrap();
+            builder.set_version(2).unwrap();
+            builder.set_subject_name(&name).unwrap();
+            builder.set_issuer_name(root.cert.subject_name()).unwrap();
+            builder.set_pubkey(&tsa_public_key).unwrap();
+            builder.set_not_before(root.cert.not_before()).unwrap();
+            builder.set_not_after(root.cert.not_after()).unwrap();
+            let context = builder.x509v3_context(Some(&root.cert), None);
+            let ski = SubjectKeyIdentifier::new().build(&context).unwrap();
+            builder.append_extension(ski).unwrap();
+            for extension in [
+                BasicConstraints::new().critical().build().unwrap(),
+                KeyUsage::new()
+                    .critical()
+                    .digital_signature()
+                    .build()
+                    .unwrap(),
+                ExtendedKeyUsage::new()
+                    .critical()
+                    .time_stamping()
+                    .build()
+                    .unwrap(),
+            ] {
+                builder.append_extension(extension).unwrap();
+            }
+            builder
+                .sign(&root.private_key, MessageDigest::sha256())
+                .unwrap();
+            builder.build()
+        };
+
+        Fixture { root, tsa, tsa_key }
+    }
+
+    fn authorities(fixture: &Fixture) -> TimestampAuthorities {
+        TimestampAuthorities::new([
+            CertificateDer::from(fixture.tsa.to_der().unwrap()),
+            CertificateDer::from(fixture.root.cert.to_der().unwrap()),
+        ])
+        .expect("failed to build authorities")
+    }
+
+    /// Issues a `TimeStampResp` over `signature`, at the current time.
+    fn timestamp(fixture: &Fixture, signature: &[u8]) -> Vec<u8> {
+        timestamp_imprint(
+            fixture,
+            MessageImprint {
+                hash_algorithm: AlgorithmIdentifierOwned {
+                    oid: ID_SHA_256,
+                    parameters: None,
+                },
+                hashed_message: OctetString::new(Sha256::digest(signature).to_vec()).unwrap(),
+            },
+        )
+    }
+
+    /// Issues a `TimeStampResp` over `message_imprint`, at the current time.
+    fn timestamp_imprint(fixture: &Fixture, message_imprint: MessageImprint) -> Vec<u8> {
+        let sha256 = AlgorithmIdentifierOwned {
+            oid: ID_SHA_256,
+            parameters: None,
+        };
+        let tst_info = TestTstInfo {
+            version: 1,
+            policy: ObjectIdentifier::new_unwrap("1.3.6.1.4.1.57264.2"),
+            message_imprint,
+            serial_number: 1,
+            gen_time: GeneralizedTime::from_system_time(std::time::SystemTime::now()).unwrap(),
+        }
+        .to_der()
+        .unwrap();
+
+        let attribute = |oid, value: Any| Attribute {
+            oid,
+            values: SetOfVec::try_from(vec![AttributeValue::from(value)]).unwrap(),
+        };
+        let attributes = SetOfVec::try_from(vec![
+            attribute(ID_CONTENT_TYPE, Any::encode_from(&ID_CT_TST_INFO).unwrap()),
+            attribute(
+                ID_MESSAGE_DIGEST,
+                Any::encode_from(&OctetString::new(Sha256::digest(&tst_info).to_vec()).unwrap())
+                    .unwrap(),
+            ),
+        ])
+        .unwrap()
+        .to_der()
+        .unwrap();
+        let mut signer = Signer::new(MessageDigest::sha384(), &fixture.tsa_key).unwrap();
+        signer.update(&attributes).unwrap();
+        let attributes_signature = signer.sign_to_vec().unwrap();
+
+        let tsa = Certificate::from_der(&fixture.tsa.to_der().unwrap()).unwrap();
+        let signer_info = SignerInfo {
+            version: 1,
+            sid: SignerIdentifier::IssuerAndSerialNumber(IssuerAndSerialNumber {
+                issuer: tsa.tbs_certificate.issuer.clone(),
+                serial_number: tsa.tbs_certificate.serial_number.clone(),
+            }),
+            digest_algorithm: sha256.clone(),
+            signed_attrs: Some(RawSet(
+                AnyRef::from_der(&attributes).unwrap().value().to_vec(),
+            )),
+            signature_algorithm: AlgorithmIdentifierOwned {
+                oid: ECDSA_WITH_SHA_384,
+                parameters: None,
+            },
+            signature: OctetString::new(attributes_signature).unwrap(),
+            unsigned_attrs: None,
+        };
+        let signed_data = SignedData {
+            version: 3,
+            digest_algorithms: SetOfVec::try_from(vec![sha256]).unwrap(),
+            encap_content_info: EncapsulatedContentInfo {
+                e_content_type: ID_CT_TST_INFO,
+                e_content: Some(OctetString::new(tst_info).unwrap()),
+            },
+            certificates: Some(SetOfVec::try_from(vec![tsa]).unwrap()),
+            crls: None,
+            signer_infos: SetOfVec::try_from(vec![signer_info]).unwrap(),
+        };
+
+        TimeStampResp {
+            status: PkiStatusInfo {
+                status: 0,
+                status_string: None,
+                fail_info: None,
+            },
+            time_stamp_token: Some(ContentInfo {
+                content_type: ID_SIGNED_DATA,
+                content: Any::encode_from(&signed_data).unwrap(),
+            }),
+        }
+        .to_der()
+        .unwrap()
+    }
+
+    /// A timestamp authority answering the `TimeStampReq`s it is sent over HTTP.
+    #[cfg(feature = "sign")]
+    pub(crate) struct FakeTimestampAuthority {
+        url: String,
+        certs: Vec<Vec<u8>>,
+    }
+
+    #[cfg(feature = "sign")]
+    impl FakeTimestampAuthority {
+        pub(crate) fn new() -> Self {
+            let fixture = fixture();
+            let certs = vec![
+                fixture.tsa.to_der().unwrap(),
+                fixture.root.cert.to_der().unwrap(),
+            ];
+            let url =
+                crate::rekor::client::tests::serve(move |request| {
+                    match TimeStampReq::from_der(&request.body) {
+                        Ok(request) => (200, timestamp_imprint(&fixture, request.message_imprint)),
+                        Err(_) => (400, Vec::new()),
+                    }
+                });
+
+            Self { url, certs }
+        }
+
+        /// Returns the URL the authority accepts `TimeStampReq`s at.
+        pub(crate) fn url(&self) -> url::Url {
+            url::Url::parse(&self.url).expect("failed to parse authority URL")
+        }
+
+        /// Returns the authority's certificate chain, from its leaf to its root.
+        pub(crate) fn certs(&self) -> Vec<CertificateDer<'static>> {
+            self.certs
+                .iter()
+                .cloned()
+                .map(CertificateDer::from)
+                .collect()
+        }
+    }
+
+    #[test]
+    fn timestamp_verifies() {
+        let fixture = fixture();
+        let response = timestamp(&fixture, b"signature");
+
+        let time = verify_timestamp(&response, b"signature", &authorities(&fixture))
+            .expect("timestamp should verify");
+        assert!((Utc::now() - time).num_seconds().abs() < 60);
+
+        // Bare tokens are accepted too.
+        let token = TimeStampResp::from_der(&response)
+            .unwrap()
+            .time_stamp_token
+            .unwrap()
+            .to_der()
+            .unwrap();
+        assert_eq!(
+            verify_timestamp(&token, b"signature", &authorities(&fixture)).unwrap(),
+            time
+        );
+    }
+
+    #[test]
+    fn timestamp_curve_from_certificate() {
+        // ECDSA-with-SHA384 names the digest algorithm, not the curve of the signing key.
+        let fixture = fixture_with_key(generate_ecdsa_p256_keypair());
+        let response = timestamp(&fixture, b"signature");
+
+        verify_timestamp(&response, b"signature", &authorities(&fixture))
+            .expect("timestamp should verify");
+    }
+
+    #[test]
+    fn timestamp_other_signature() {
+        let fixture = fixture();
+        let response = timestamp(&fixture, b"signature");
+
+        assert!(matches!(
+            verify_timestamp(&response, b"other signature", &authorities(&fixture)),
+            Err(TimestampError::MessageImprintMismatch)
+        ));
+    }
+
+    #[test]
+    fn timestamp_untrusted_authority() {
+        let fixture = fixture();
+        let response = timestamp(&fixture, b"signature");
+        let other = generate_certificate(None, Default::default()).unwrap();
+        let authorities =
+            TimestampAuthorities::new([CertificateDer::from(other.cert.to_der().unwrap())])
+                .unwrap();
+
+        assert!(matches!(
+            verify_timestamp(&response, b"signature", &authorities),
+            Err(TimestampError::CertificateVerificationFailed(_))
+        ));
+    }
+
+    #[rstest::rstest]
+    #[case::open(None, None, true)]
+    #[case::started(Some(-1), None, true)]
+    #[case::current(Some(-1), Some(1), true)]
+    #[case::not_started(Some(1), None, false)]
+    #[case::ended(None, Some(-1), false)]
+    fn timestamp_authority_validity(
+        #[case] start: Option<i64>,
+        #[case] end: Option<i64>,
+        #[case] verifies: bool,
+    ) {
+        let fixture = fixture();
+        let response = timestamp(&fixture, b"signature");
+        let hours_from_now = |hours| Utc::now() + chrono::TimeDelta::hours(hours);
+        let authorities = TimestampAuthorities::from_authorities([CertificateAuthority {
+            cert_chain: vec![
+                CertificateDer::from(fixture.tsa.to_der().unwrap()),
+                CertificateDer::from(fixture.root.cert.to_der().unwrap()),
+            ],
+            valid_for: ValidityPeriod {
+                start: start.map(hours_from_now),
+                end: end.map(hours_from_now),
+            },
+        }])
+        .expect("failed to build authorities");
+
+        let result = verify_timestamp(&response, b"signature", &authorities);
+        match verifies {
+            true => assert!(result.is_ok()),
+            false => assert!(matches!(
+                result,
+                Err(TimestampError::OutsideAuthorityValidity)
+            )),
+        }
+    }
+
+    #[test]
+    fn timestamp_tampered() {
+        let fixture = fixture();
+        let mut response = timestamp(&fixture, b"signature");
+        // Flip a bit in the token's signature, at the end of the response.
+        *response.last_mut().unwrap() ^= 1;
+
+        assert!(verify_timestamp(&response, b"signature", &authorities(&fixture)).is_err());
+        assert!(matches!(
+            verify_timestamp(b"garbage", b"signature", &authorities(&fixture)),
+            Err(TimestampError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn timestamp_response_check() {
+        let fixture = fixture();
+        let response = timestamp(&fixture, b"signature");
+
+        assert!(check_timestamp_response(&response, b"signature").is_ok());
+        assert!(matches!(
+            check_timestamp_response(&response, b"other signature"),
+            Err(TimestampError::MessageImprintMismatch)
+        ));
+
+        let request = TimeStampReq::from_der(&timestamp_request(b"signature").unwrap()).unwrap();
+        assert_eq!(request.message_imprint.hash_algorithm.oid, ID_SHA_256);
+        assert_eq!(
+            request.message_imprint.hashed_message.as_bytes(),
+            &Sha256::digest(b"signature")[..]
+        );
+        assert!(request.cert_req);
+    }
+
+    #[test]
+    fn generalized_time() {
+        let time = |s: &str| parse_generalized_time(s.as_bytes()).map(|t| t.timestamp());
+
+        assert_eq!(time("20240101000000Z"), Some(1_704_067_200));
+        assert_eq!(time("20240101000000.123Z"), Some(1_704_067_200));
+        assert_eq!(time("20240101000000"), None);
+        assert_eq!(time("20240101000000.Z1"), None);
+        assert_eq!(time("202401010000Z"), None);
+    }
+}
@@ -0,0 +1,399 @@
+//
+// Copyright 2024 The Sigstore Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of Certificate Transparency Signed Certificate Timestamps (SCTs), as specified by
+//! [RFC 6962](https://www.rfc-editor.org/rfc/rfc6962#section-3.2).
+
+use const_oid::AssociatedOid;
+use pkcs8::der::{Decode, Encode};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tls_codec::{SerializeBytes, TlsByteVecU16, TlsByteVecU24, TlsSerializeBytes, TlsSize};
+use x509_cert::{
+    ext::pkix::{
+        sct::{SignedCertificateTimestamp, Version},
+        SignedCertificateTimestampList,
+    },
+    Certificate,
+};
+
+#[cfg(feature = "fulcio")]
+use crate::fulcio::{InnerDetachedSCT, SCTVersion};
+
+use super::keyring::{Keyring, KeyringError};
+
+#[derive(Error, Debug)]
+pub enum SCTError {
+    #[error("certificate does not contain an embedded SCT")]
+    SCTMissing,
+
+    #[error("SCT is malformed: {0}")]
+    SCTMalformed(String),
+
+    #[error("issuer of the certificate could not be found")]
+    IssuerNotFound,
+
+    #[error("SCT verification failed")]
+    VerificationFailed(#[source] KeyringError),
+}
+
+#[derive(TlsSerializeBytes, TlsSize)]
+#[repr(u8)]
+enum SignatureType {
+    CertificateTimestamp = 0,
+}
+
+#[derive(TlsSerializeBytes, TlsSize)]
+struct PreCert {
+    issuer_key_hash: [u8; 32],
+    tbs_certificate: TlsByteVecU24,
+}
+
+#[derive(TlsSerializeBytes, TlsSize)]
+#[repr(u16)]
+enum SignedEntry {
+    #[tls_codec(discriminant = 0)]
+    X509Certificate(TlsByteVecU24),
+    #[tls_codec(discriminant = 1)]
+    PreCertificate(PreCert),
+}
+
+/// The data covered by the signature of an SCT.
+#[derive(TlsSerializeBytes, TlsSize)]
+struct DigitallySignedStruct {
+    version: Version,
+    signature_type: SignatureType,
+    timestamp: u64,
+    signed_entry: SignedEntry,
+    extensions: TlsByteVecU16,
+}
+
+fn malformed(err: impl std::fmt::Debug) -> SCTError {
+    SCTError::SCTMalformed(format!("{err:?}"))
+}
+
+fn verify_sct(
+    keyring: &Keyring,
+    log_id: &[u8],
+    signature: &[u8],
+    signed: DigitallySignedStruct,
+) -> Result<(), SCTError> {
+    // The log key must have been valid when the SCT was issued.
+    let timestamp = i64::try_from(signed.timestamp)
+        .ok()
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .ok_or_else(|| malformed("SCT timestamp out of range"))?;
+    let signed = signed.tls_serialize().map_err(malformed)?;
+
+    keyring
+        .verify_at(log_id, signature, &signed, timestamp)
+        .map_err(SCTError::VerificationFailed)
+}
+
+/// Verifies the SCTs embedded in `cert` against the CT log keys in `keyring`.
+///
+/// Embedded SCTs are issued over the precertificate, which binds them to the certificate's issuer:
+/// the issuer is looked up by subject in `issuers`. Verification succeeds if any of the embedded
+/// SCTs verifies.
+pub fn verify_embedded_sct(
+    cert: &Certificate,
+    issuers: &[Certificate],
+    keyring: &Keyring,
+) -> Result<(), SCTError> {
+    let tbs = &cert.tbs_certificate;
+    let is_sct_list =
+        |ext: &x509_cert::ext::Extension| ext.extn_id == SignedCertificateTimestampList::OID;
+
+    let sct_list = tbs
+        .extensions
+        .iter()
+        .flatten()
+        .find(|ext| is_sct_list(ext))
+        .ok_or(SCTError::SCTMissing)?;
+    let scts = SignedCertificateTimestampList::from_der(sct_list.extn_value.as_bytes())
+        .map_err(malformed)?
+        .parse_timestamps()
+        .map_err(malformed)?
+        .iter()
+        .map(|sct| sct.parse_timestamp())
+        .collect::<Result<Vec<SignedCertificateTimestamp>, _>>()
+        .map_err(malformed)?;
+
+    let issuer = issuers
+        .iter()
+        .find(|issuer| issuer.tbs_certificate.subject == tbs.issuer)
+        .ok_or(SCTError::IssuerNotFound)?;
+    let issuer_key_hash: [u8; 32] = Sha256::digest(
+        issuer
+            .tbs_certificate
+            .subject_public_key_info
+            .to_der()
+            .map_err(malformed)?,
+    )
+    .into();
+
+    // The precertificate is the certificate without its SCT list extension.
+    let mut precert = tbs.clone();
+    precert.extensions = precert
+        .extensions
+        .map(|exts| exts.into_iter().filter(|ext| !is_sct_list(ext)).collect());
+    let precert = precert.to_der().map_err(malformed)?;
+
+    let mut result = Err(SCTError::SCTMissing);
+    for sct in scts {
+        let signed = DigitallySignedStruct {
+            version: sct.version,
+            signature_type: SignatureType::CertificateTimestamp,
+            timestamp: sct.timestamp,
+            signed_entry: SignedEntry::PreCertificate(PreCert {
+                issuer_key_hash,
+                tbs_certificate: TlsByteVecU24::from_slice(&precert),
+            }),
+            extensions: sct.extensions,
+        };
+
+        result = verify_sct(
+            keyring,
+            &sct.log_id.key_id,
+            sct.signature.signature.as_slice(),
+            signed,
+        );
+        if result.is_ok() {
+            break;
+        }
+    }
+
+    result
+}
+
+/// Verifies a detached SCT issued by Fulcio for `cert` against the CT log keys in `keyring`.
+#[cfg(feature = "fulcio")]
+pub fn verify_detached_sct(
+    cert: &Certificate,
+    sct: &InnerDetachedSCT,
+    keyring: &Keyring,
+) -> Result<(), SCTError> {
+    let version = match sct.sct_version {
+        SCTVersion::V1 => Version::V1,
+    };
+    let signed = DigitallySignedStruct {
+        version,
+        signature_type: SignatureType::CertificateTimestamp,
+        timestamp: sct.timestamp,
+        signed_entry: SignedEntry::X509Certificate(TlsByteVecU24::new(
+            cert.to_der().map_err(malformed)?,
+        )),
+        extensions: TlsByteVecU16::from_slice(&sct.extensions),
+    };
+
+    verify_sct(keyring, &sct.id, &sct.signature, signed)
+}
+
+#[cfg(test)]
+mod tests {
+    use x509_cert::ext::{
+        pkix::sct::{
+            DigitallySigned, HashAlgorithm, LogId, SerializedSct, SignatureAlgorithm,
+            SignatureAndHashAlgorithm,
+        },
+        Extension,
+    };
+
+    use super::*;
+    use crate::crypto::{
+        keyring::key_id,
+        tests::{generate_certificate, CertGenerationOptions},
+        SigStoreSigner, SigningScheme,
+    };
+
+    struct Fixture {
+        issuer: Certificate,
+        cert: Certificate,
+        log: SigStoreSigner,
+        keyring: Keyring,
+        log_id: [u8; 32],
+    }
+
+    fn fixture() -> Fixture {
+        let issuer = generate_certificate(None, CertGenerationOptions::default())
+            .expect("failed to generate issuer");
+        let cert = generate_certificate(Some(&issuer), CertGenerationOptions::default())
+            .expect("failed to generate certificate");
+        let to_x509 = |cert: &openssl::x509::X509| {
+            Certificate::from_der(&cert.to_der().expect("failed to encode certificate"))
+                .expect("failed to decode certificate")
+        };
+
+        let log = SigningScheme::ECDSA_P256_SHA256_ASN1
+            .create_signer()
+            .expect("failed to create signer");
+        let log_key = log
+            .to_sigstore_keypair()
+            .and_then(|k| k.public_key_to_der())
+            .expect("failed to export public key");
+
+        Fixture {
+            issuer: to_x509(&issuer.cert),
+            cert: to_x509(&cert.cert),
+            log,
+            keyring: Keyring::new([log_key.as_slice()]).expect("failed to build keyring"),
+            log_id: key_id(&log_key),
+        }
+    }
+
+    /// Signs a precertificate SCT for `fixture.cert` and embeds it.
+    fn embed_sct(fixture: &mut Fixture) {
+        let issuer_key_hash = Sha256::digest(
+            fixture
+                .issuer
+                .tbs_certificate
+                .subject_public_key_info
+                .to_der()
+                .unwrap(),
+        )
+        .into();
+        let signed = DigitallySignedStruct {
+            version: Version::V1,
+            signature_type: SignatureType::CertificateTimestamp,
+            timestamp: 1_700_000_000_000,
+            signed_entry: SignedEntry::PreCertificate(PreCert {
+                issuer_key_hash,
+                tbs_certificate: TlsByteVecU24::new(fixture.cert.tbs_certificate.to_der().unwrap()),
+            }),
+            extensions: TlsByteVecU16::new(vec![]),
+        };
+        let signature = fixture
+            .log
+            .sign(&signed.tls_serialize().unwrap())
+            .expect("failed to sign");
+
+        let sct = SignedCertificateTimestamp {
+            version: Version::V1,
+            log_id: LogId {
+                key_id: fixture.log_id,
+            },
+            timestamp: 1_700_000_000_000,
+            extensions: TlsByteVecU16::new(vec![]),
+            signature: DigitallySigned {
+                algorithm: SignatureAndHashAlgorithm {
+                    hash: HashAlgorithm::Sha256,
+                    signature: SignatureAlgorithm::Ecdsa,
+                },
+                signature: TlsByteVecU16::new(signature),
+            },
+        };
+        let sct_list =
+            SignedCertificateTimestampList::new(&[SerializedSct::new(sct).unwrap()]).unwrap();
+
+        fixture
+            .cert
+            .tbs_certificate
+            .extensions
+            .get_or_insert_with(Vec::new)
+            .push(Extension {
+                extn_id: SignedCertificateTimestampList::OID,
+                critical: false,
+                extn_value: pkcs8::der::asn1::OctetString::new(sct_list.to_der().unwrap()).unwrap(),
+            });
+    }
+
+    #[test]
+    fn embedded_sct_verifies() {
+        let mut fixture = fixture();
+        embed_sct(&mut fixture);
+
+        assert!(verify_embedded_sct(&fixture.cert, &[fixture.issuer], &fixture.keyring).is_ok());
+    }
+
+    #[test]
+    fn embedded_sct_missing() {
+        let fixture = fixture();
+
+        assert!(matches!(
+            verify_embedded_sct(&fixture.cert, &[fixture.issuer], &fixture.keyring),
+            Err(SCTError::SCTMissing)
+        ));
+    }
+
+    #[test]
+    fn embedded_sct_unknown_issuer() {
+        let mut fixture = fixture();
+        embed_sct(&mut fixture);
+
+        assert!(matches!(
+            verify_embedded_sct(&fixture.cert, &[], &fixture.keyring),
+            Err(SCTError::IssuerNotFound)
+        ));
+    }
+
+    #[test]
+    fn embedded_sct_tampered_certificate() {
+        let mut fixture = fixture();
+        embed_sct(&mut fixture);
+        fixture.cert.tbs_certificate.serial_number =
+            x509_cert::serial_number::SerialNumber::new(&[0x42]).unwrap();
+
+        assert!(matches!(
+            verify_embedded_sct(&fixture.cert, &[fixture.issuer], &fixture.keyring),
+            Err(SCTError::VerificationFailed(
+                KeyringError::VerificationFailed(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn embedded_sct_unknown_log() {
+        let mut fixture = fixture();
+        embed_sct(&mut fixture);
+
+        assert!(matches!(
+            verify_embedded_sct(&fixture.cert, &[fixture.issuer], &Keyring::default()),
+            Err(SCTError::VerificationFailed(KeyringError::KeyNotFound(_)))
+        ));
+    }
+
+    #[cfg(feature = "fulcio")]
+    #[test]
+    fn detached_sct_verifies() {
+        let fixture = fixture();
+        let signed = DigitallySignedStruct {
+            version: Version::V1,
+            signature_type: SignatureType::CertificateTimestamp,
+            timestamp: 1_700_000_000_000,
+            signed_entry: SignedEntry::X509Certificate(TlsByteVecU24::new(
+                fixture.cert.to_der().unwrap(),
+            )),
+            extensions: TlsByteVecU16::new(vec![]),
+        };
+        let mut sct = InnerDetachedSCT {
+            sct_version: SCTVersion::V1,
+            id: fixture.log_id,
+            timestamp: 1_700_000_000_000,
+            signature: fixture
+                .log
+                .sign(&signed.tls_serialize().unwrap())
+                .expect("failed to sign"),
+            extensions: vec![],
+        };
+
+        assert!(verify_detached_sct(&fixture.cert, &sct, &fixture.keyring).is_ok());
+
+        sct.timestamp += 1;
+        assert!(matches!(
+            verify_detached_sct(&fixture.cert, &sct, &fixture.keyring),
+            Err(SCTError::VerificationFailed(_))
+        ));
+    }
+}
@@ -58,6 +58,9 @@ pub enum SigstoreError {
     #[error("unmatched key type {key_typ} and signing scheme {scheme}")]
     UnmatchedKeyAndSigningScheme { key_typ: String, scheme: String },
 
+    #[error("signing scheme {0} cannot sign message digests")]
+    PrehashSigningUnsupported(String),
+
     #[error("x509 error: {0}")]
     X509Error(String),
 
@@ -130,9 +133,27 @@ pub enum SigstoreError {
     #[error("Rekor request unsuccessful: {0}")]
     RekorClientError(String),
 
+    #[cfg(feature = "rekor")]
+    #[error(transparent)]
+    RekorError(#[from] Box<crate::rekor::client::RekorError>),
+
+    #[cfg(feature = "rekor")]
+    #[error(transparent)]
+    LogEntryError(#[from] Box<crate::rekor::models::log_entry::LogEntryError>),
+
+    #[error("Timestamp authority request unsuccessful: {0}")]
+    TimestampAuthorityClientError(String),
+
     #[error(transparent)]
     JoinError(#[from] tokio::task::JoinError),
 
+    #[error("SCTs of Fulcio certificates cannot be verified: no CT log keys are configured")]
+    CtfeKeyringMissingError,
+
+    #[cfg(feature = "cert")]
+    #[error(transparent)]
+    SCTError(#[from] Box<crate::crypto::transparency::SCTError>),
+
     // HACK(tnytown): Remove when we rework the Fulcio V2 endpoint.
     #[cfg(feature = "fulcio")]
     #[error(transparent)]
@@ -144,6 +165,9 @@ pub enum SigstoreError {
     #[error("Sigstore bundle malformed: {0}")]
     SigstoreBundleMalformedError(String),
 
+    #[error("Signing config error: {0}")]
+    SigningConfigError(String),
+
     #[error("Layer doesn't have Sigstore media type")]
     SigstoreMediaTypeNotFoundError,
 
@@ -250,3 +274,10 @@ pub enum SigstoreError {
     #[error(transparent)]
     X509BuilderError(#[from] x509_cert::builder::Error),
 }
+
+#[cfg(feature = "rekor")]
+impl From<crate::rekor::models::log_entry::LogEntryError> for SigstoreError {
+    fn from(err: crate::rekor::models::log_entry::LogEntryError) -> Self {
+        Self::LogEntryError(Box::new(err))
+    }
+}
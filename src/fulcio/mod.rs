@@ -7,7 +7,8 @@ use crate::crypto::SigningScheme;
 use crate::errors::{Result, SigstoreError};
 use crate::fulcio::models::{CreateSigningCertificateRequest, SigningCertificate};
 use crate::fulcio::oauth::OauthTokenProvider;
-use crate::oauth::IdentityToken;
+use crate::oauth::{IdentityToken, IdentityTokenSource};
+use async_trait::async_trait;
 use base64::{engine::general_purpose::STANDARD as BASE64_STD_ENGINE, Engine as _};
 use openidconnect::core::CoreIdToken;
 use reqwest::{header, Body};
@@ -17,7 +18,9 @@ use std::fmt::{Debug, Display, Formatter};
 use url::Url;
 use x509_cert::{der::Decode, Certificate};
 
-pub use models::{CertificateResponse, SigningCertificateDetachedSCT};
+pub use models::{
+    CertificateResponse, InnerDetachedSCT, SCTVersion, SigningCertificateDetachedSCT,
+};
 
 /// Default public Fulcio server root.
 pub const FULCIO_ROOT: &str = "https://fulcio.sigstore.dev/";
@@ -134,6 +137,14 @@ impl TokenProvider {
     }
 }
 
+#[async_trait]
+impl IdentityTokenSource for TokenProvider {
+    async fn identity_token(&self) -> Result<IdentityToken> {
+        let (token, _) = self.get_token().await?;
+        token.to_string().as_str().try_into()
+    }
+}
+
 /// Client for creating and holding ephemeral key pairs, and easily
 /// getting a Fulcio-signed certificate chain.
 pub struct FulcioClient {
@@ -16,4 +16,4 @@
 pub mod openidflow;
 
 mod token;
-pub use token::IdentityToken;
+pub use token::{IdentityToken, IdentityTokenSource};
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use openidconnect::core::CoreIdToken;
 use serde::Deserialize;
@@ -60,6 +61,17 @@ impl IdentityToken {
     }
 }
 
+/// A source of Sigstore identity tokens, such as an OIDC flow or the ambient credentials of a
+/// CI environment.
+///
+/// Sources are queried for a fresh token each time a refreshing signing session renews its
+/// identity.
+#[async_trait]
+pub trait IdentityTokenSource: Send + Sync {
+    /// Acquires a new identity token.
+    async fn identity_token(&self) -> Result<IdentityToken, SigstoreError>;
+}
+
 impl TryFrom<&str> for IdentityToken {
     type Error = SigstoreError;
 
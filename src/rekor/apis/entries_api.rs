@@ -60,11 +60,25 @@ pub struct LogEntries {
 // TEMPORARY: Formats the returned response such that it can be read into a struct
 // TODO: Remove once upstream issue around dynamic top level key is resolved:
 // https://github.com/sigstore/rekor/issues/808
+//
+// Rekor returns entries keyed by their UUID, as in `{"<uuid>": {...}}`: the UUID is moved into
+// the entry. Responses of any other shape are returned unchanged, for the log entry parser to
+// reject.
 pub fn parse_response(local_var_content: String) -> String {
-    let uuid: &str = &local_var_content[1..82];
-    let rest: &str = &local_var_content[85..local_var_content.len() - 2];
-
-    "{\"uuid\":".to_string() + uuid + "\"," + rest
+    let Ok(serde_json::Value::Object(response)) =
+        serde_json::from_str::<serde_json::Value>(&local_var_content)
+    else {
+        return local_var_content;
+    };
+    let mut entries = response.into_iter();
+    let (Some((uuid, serde_json::Value::Object(mut entry))), None) =
+        (entries.next(), entries.next())
+    else {
+        return local_var_content;
+    };
+
+    entry.insert("uuid".into(), uuid.into());
+    serde_json::Value::Object(entry).to_string()
 }
 
 /// Creates an entry in the transparency log for a detached signature, public key, and content. Items can be included in the request or fetched by the server when URLs are specified.
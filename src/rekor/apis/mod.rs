@@ -8,6 +8,7 @@ pub struct ResponseContent<T> {
 }
 
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum Error<T> {
     #[error("error in reqwest: {source:?}")]
     Reqwest {
@@ -27,6 +28,12 @@ pub enum Error<T> {
         source: std::io::Error,
     },
 
+    #[error("error in log entry: {source}")]
+    LogEntry {
+        #[from]
+        source: crate::rekor::models::log_entry::LogEntryError,
+    },
+
     #[error("error in response: status code {:?}", error_status(.0))]
     ResponseError(ResponseContent<T>),
 }
@@ -0,0 +1,656 @@
+//
+// Copyright 2024 The Sigstore Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A typed client for the Rekor API.
+//!
+//! [`RekorClient`] wraps the functions of the [`apis`](crate::rekor::apis) module, building
+//! requests from typed arguments and reporting every failure as a [`RekorError`].
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), sigstore::rekor::client::RekorError> {
+//! use sigstore::rekor::client::RekorClient;
+//!
+//! let client = RekorClient::default();
+//! let log_info = client.log_info().await?;
+//! let entry = client.entry_by_index(log_info.tree_size as u64 - 1).await?;
+//! println!("{:#?}", entry);
+//! # Ok(())
+//! # }
+//! ```
+
+use base64::{engine::general_purpose::STANDARD as BASE64_STD_ENGINE, Engine as _};
+use reqwest::StatusCode;
+use thiserror::Error;
+
+use crate::crypto::keyring::Keyring;
+use crate::rekor::apis::configuration::Configuration;
+use crate::rekor::apis::{entries_api, index_api, tlog_api, Error as ApiError};
+use crate::rekor::models::search_index_public_key::Format;
+use crate::rekor::models::{
+    hashedrekord,
+    log_entry::{uuid_leaf_hash, LogEntryError},
+    Error as ErrorModel, LogEntry, LogInfo, ProposedEntry, SearchIndex, SearchIndexPublicKey,
+};
+
+#[derive(Error, Debug)]
+pub enum RekorError {
+    #[error("request to Rekor failed")]
+    Request(#[source] reqwest::Error),
+
+    #[error("Rekor responded with status {status}: {message}")]
+    Response { status: StatusCode, message: String },
+
+    #[error("Rekor response is malformed: {0}")]
+    ResponseMalformed(String),
+
+    #[error("log index {0} is out of range")]
+    LogIndexOutOfRange(u64),
+
+    #[error("log entry verification failed")]
+    Verification(#[from] LogEntryError),
+
+    #[error("Rekor returned log entry {returned}, not the requested {requested}")]
+    EntryMismatch { requested: String, returned: String },
+}
+
+impl RekorError {
+    /// Returns the HTTP status of the Rekor response, if Rekor responded with an error.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            RekorError::Response { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl<T> From<ApiError<T>> for RekorError {
+    fn from(err: ApiError<T>) -> Self {
+        match err {
+            ApiError::Reqwest { source } => RekorError::Request(source),
+            ApiError::Serde { source } => RekorError::ResponseMalformed(source.to_string()),
+            ApiError::Io { source } => RekorError::ResponseMalformed(source.to_string()),
+            ApiError::LogEntry { source } => RekorError::ResponseMalformed(source.to_string()),
+            ApiError::ResponseError(response) => {
+                // Rekor describes its errors with an `Error` model; fall back to the raw body.
+                let message = serde_json::from_str::<ErrorModel>(&response.content)
+                    .ok()
+                    .and_then(|error| error.message)
+                    .unwrap_or_else(|| response.content.trim().to_owned());
+
+                RekorError::Response {
+                    status: response.status,
+                    message,
+                }
+            }
+        }
+    }
+}
+
+/// A client for a Rekor transparency log.
+///
+/// By default, the log entries returned by the client are not verified. Use
+/// [`RekorClient::with_keyring`] to only accept entries signed by the log.
+#[derive(Default)]
+pub struct RekorClient {
+    configuration: Configuration,
+    keyring: Option<Keyring>,
+}
+
+impl RekorClient {
+    /// Constructs a client for the Rekor instance described by `configuration`.
+    ///
+    /// Use [`RekorClient::default`] for the public-good Rekor instance.
+    pub fn new(configuration: Configuration) -> Self {
+        Self {
+            configuration,
+            keyring: None,
+        }
+    }
+
+    /// Configures the client to verify every log entry it returns against the log keys held in
+    /// `keyring`. See [`LogEntry::verify`] for the checks performed.
+    pub fn with_keyring(mut self, keyring: Keyring) -> Self {
+        self.keyring = Some(keyring);
+        self
+    }
+
+    /// Returns the configuration of the client.
+    pub fn configuration(&self) -> &Configuration {
+        &self.configuration
+    }
+
+    /// Uploads `proposed_entry` to the log, returning the resulting entry.
+    pub async fn upload(&self, proposed_entry: ProposedEntry) -> Result<LogEntry, RekorError> {
+        let entry = entries_api::create_log_entry(&self.configuration, proposed_entry).await?;
+
+        self.verified(entry)
+    }
+
+    /// Uploads a `hashedrekord` entry to the log, recording `signature` over the artifact
+    /// whose digest is `digest`.
+    ///
+    /// `public_key` is the PEM-encoded public key or certificate verifying `signature`.
+    pub async fn upload_hashedrekord(
+        &self,
+        signature: &[u8],
+        public_key: &str,
+        digest: hashedrekord::Hash,
+    ) -> Result<LogEntry, RekorError> {
+        let proposed_entry = ProposedEntry::Hashedrekord {
+            api_version: "0.0.1".to_owned(),
+            spec: hashedrekord::Spec::new(
+                hashedrekord::Signature::new(
+                    BASE64_STD_ENGINE.encode(signature),
+                    hashedrekord::PublicKey::new(BASE64_STD_ENGINE.encode(public_key)),
+                ),
+                hashedrekord::Data::new(digest),
+            ),
+        };
+
+        self.upload(proposed_entry).await
+    }
+
+    /// Fetches the entry at `log_index` from the log.
+    pub async fn entry_by_index(&self, log_index: u64) -> Result<LogEntry, RekorError> {
+        let index = log_index
+            .try_into()
+            .or(Err(RekorError::LogIndexOutOfRange(log_index)))?;
+
+        let entry = entries_api::get_log_entry_by_index(&self.configuration, index).await?;
+        if entry.log_index != i64::from(index) {
+            return Err(RekorError::EntryMismatch {
+                requested: format!("at index {log_index}"),
+                returned: format!("at index {}", entry.log_index),
+            });
+        }
+
+        self.verified(entry)
+    }
+
+    /// Fetches the entry identified by `uuid` from the log. The tree ID prefix of UUIDs in
+    /// sharded logs is optional.
+    pub async fn entry_by_uuid(&self, uuid: &str) -> Result<LogEntry, RekorError> {
+        let entry = entries_api::get_log_entry_by_uuid(&self.configuration, uuid).await?;
+        if uuid_leaf_hash(&entry.uuid).is_none()
+            || uuid_leaf_hash(&entry.uuid) != uuid_leaf_hash(uuid)
+        {
+            return Err(RekorError::EntryMismatch {
+                requested: uuid.to_owned(),
+                returned: entry.uuid,
+            });
+        }
+
+        self.verified(entry)
+    }
+
+    /// Verifies `entry` if the client holds the log's keys.
+    fn verified(&self, entry: LogEntry) -> Result<LogEntry, RekorError> {
+        if let Some(keyring) = &self.keyring {
+            entry.verify(keyring)?;
+        }
+
+        Ok(entry)
+    }
+
+    /// Returns the UUIDs of the entries recording an artifact with the given digest.
+    ///
+    /// `hash` is the hex-encoded digest, optionally prefixed with its algorithm, as in
+    /// `sha256:<digest>`.
+    pub async fn search_by_hash(&self, hash: &str) -> Result<Vec<String>, RekorError> {
+        self.search(SearchIndex {
+            hash: Some(hash.to_owned()),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Returns the UUIDs of the entries signed by certificates issued to `email`.
+    pub async fn search_by_email(&self, email: &str) -> Result<Vec<String>, RekorError> {
+        self.search(SearchIndex {
+            email: Some(email.to_owned()),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Returns the UUIDs of the entries verifiable with `public_key`, encoded in `format`.
+    pub async fn search_by_public_key(
+        &self,
+        format: Format,
+        public_key: &[u8],
+    ) -> Result<Vec<String>, RekorError> {
+        self.search(SearchIndex {
+            public_key: Some(SearchIndexPublicKey {
+                content: Some(BASE64_STD_ENGINE.encode(public_key)),
+                ..SearchIndexPublicKey::new(format)
+            }),
+            ..Default::default()
+        })
+        .await
+    }
+
+    async fn search(&self, query: SearchIndex) -> Result<Vec<String>, RekorError> {
+        Ok(index_api::search_index(&self.configuration, query).await?)
+    }
+
+    /// Returns the current state of the log.
+    pub async fn log_info(&self) -> Result<LogInfo, RekorError> {
+        Ok(tlog_api::get_log_info(&self.configuration).await?)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    use json_syntax::Print;
+    use serde_json::{json, Value};
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+    use crate::crypto::{merkle, SigStoreSigner};
+    use crate::rekor::apis::entries_api::CreateLogEntryError;
+    use crate::rekor::apis::ResponseContent;
+    use crate::rekor::models::checkpoint::tests::{log_signer, sign};
+
+    /// An HTTP request received by a server started with [`serve`].
+    pub(crate) struct Request {
+        pub method: String,
+        /// The path of the request, including its query.
+        pub path: String,
+        pub body: Vec<u8>,
+    }
+
+    /// Serves HTTP requests on a local port, answering each with the status and body returned
+    /// by `handler`. Returns the base URL of the server.
+    pub(crate) fn serve<H, B>(handler: H) -> String
+    where
+        H: Fn(Request) -> (u16, B) + Send + Sync + 'static,
+        B: Into<Vec<u8>>,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind test server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handler = Arc::new(handler);
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let handler = handler.clone();
+                std::thread::spawn(move || {
+                    let mut reader = BufReader::new(&stream);
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).unwrap_or_default();
+                    let mut request_line = request_line.split_whitespace();
+                    let (Some(method), Some(path)) = (request_line.next(), request_line.next())
+                    else {
+                        return;
+                    };
+
+                    let mut content_length = 0;
+                    loop {
+                        let mut header = String::new();
+                        reader.read_line(&mut header).unwrap_or_default();
+                        match header.trim_end().split_once(':') {
+                            Some((name, value)) if name.eq_ignore_ascii_case("content-length") => {
+                                content_length = value.trim().parse().unwrap_or_default();
+                            }
+                            Some(_) => (),
+                            None => break,
+                        }
+                    }
+                    let mut body = vec![0; content_length];
+                    if reader.read_exact(&mut body).is_err() {
+                        return;
+                    }
+
+                    let (status, body) = handler(Request {
+                        method: method.to_owned(),
+                        path: path.to_owned(),
+                        body,
+                    });
+                    let body = body.into();
+                    let reason = StatusCode::from_u16(status)
+                        .ok()
+                        .and_then(|status| status.canonical_reason())
+                        .unwrap_or_default();
+                    let _ = write!(
+                        &stream,
+                        "HTTP/1.1 {status} {reason}\r\n\
+                         Content-Type: application/json\r\n\
+                         Content-Length: {}\r\n\
+                         Connection: close\r\n\r\n",
+                        body.len()
+                    )
+                    .and_then(|_| (&stream).write_all(&body));
+                });
+            }
+        });
+
+        url
+    }
+
+    /// How a [`FakeRekor`] answers an upload.
+    pub(crate) enum Upload {
+        /// Integrate the entry in the log, and return it.
+        Accept,
+        /// Reject the entry with the given status.
+        Reject(u16),
+        /// Integrate the entry in the log, but answer with the given status, as if the response
+        /// had been lost.
+        AcceptAndFail(u16),
+    }
+
+    type UploadHook = dyn Fn(&Value, usize) -> Upload + Send + Sync;
+
+    struct FakeLog {
+        signer: SigStoreSigner,
+        log_id: [u8; 32],
+        /// The canonicalized bodies of the integrated entries, and the entries themselves.
+        entries: Vec<(Vec<u8>, String, Value)>,
+        /// The number of times each proposed entry was uploaded.
+        attempts: HashMap<String, usize>,
+        hook: Box<UploadHook>,
+    }
+
+    /// A Rekor instance serving the entries uploaded to it from memory.
+    ///
+    /// Every entry is integrated as the single leaf of a tree of its own, which keeps inclusion
+    /// proofs trivial while still committing to the entry's body.
+    pub(crate) struct FakeRekor {
+        url: String,
+        public_key: Vec<u8>,
+        log: Arc<Mutex<FakeLog>>,
+    }
+
+    impl FakeRekor {
+        pub(crate) fn new() -> Self {
+            Self::with_uploads(|_, _| Upload::Accept)
+        }
+
+        /// Starts a fake Rekor answering uploads as told by `hook`, which is given each proposed
+        /// entry and the number of times it was uploaded, this upload included.
+        pub(crate) fn with_uploads<H>(hook: H) -> Self
+        where
+            H: Fn(&Value, usize) -> Upload + Send + Sync + 'static,
+        {
+            let (signer, _, log_id) = log_signer();
+            let public_key = signer
+                .to_sigstore_keypair()
+                .and_then(|k| k.public_key_to_der())
+                .expect("failed to export public key");
+            let log = Arc::new(Mutex::new(FakeLog {
+                signer,
+                log_id,
+                entries: Vec::new(),
+                attempts: HashMap::new(),
+                hook: Box::new(hook),
+            }));
+
+            let url = serve({
+                let log = log.clone();
+                move |request| log.lock().unwrap().handle(request)
+            });
+
+            Self {
+                url,
+                public_key,
+                log,
+            }
+        }
+
+        pub(crate) fn configuration(&self) -> Configuration {
+            Configuration {
+                base_path: self.url.clone(),
+                ..Default::default()
+            }
+        }
+
+        /// Returns the DER-encoded public key of the log.
+        pub(crate) fn public_key(&self) -> &[u8] {
+            &self.public_key
+        }
+
+        /// Returns the number of uploads the log received.
+        pub(crate) fn uploads(&self) -> usize {
+            self.log.lock().unwrap().attempts.values().sum()
+        }
+    }
+
+    impl FakeLog {
+        fn handle(&mut self, request: Request) -> (u16, String) {
+            let not_found = || {
+                (
+                    404,
+                    json!({"code": 404, "message": "not found"}).to_string(),
+                )
+            };
+
+            match (request.method.as_str(), request.path.as_str()) {
+                ("POST", "/api/v1/log/entries") => {
+                    let Ok(proposed) = serde_json::from_slice::<Value>(&request.body) else {
+                        return (400, json!({"code": 400}).to_string());
+                    };
+                    let attempt = self.attempts.entry(proposed.to_string()).or_default();
+                    *attempt += 1;
+
+                    match (self.hook)(&proposed, *attempt) {
+                        Upload::Accept => self.integrate(&proposed),
+                        Upload::Reject(status) => (status, json!({"code": status}).to_string()),
+                        Upload::AcceptAndFail(status) => {
+                            self.integrate(&proposed);
+                            (status, json!({"code": status}).to_string())
+                        }
+                    }
+                }
+                ("GET", path) => {
+                    let entry = match path.strip_prefix("/api/v1/log/entries") {
+                        Some(query) if query.starts_with("?logIndex=") => query[10..]
+                            .parse::<usize>()
+                            .ok()
+                            .and_then(|index| self.entries.get(index)),
+                        Some(uuid) if uuid.starts_with('/') => self
+                            .entries
+                            .iter()
+                            .find(|(_, entry_uuid, _)| *entry_uuid == uuid[1..]),
+                        _ => None,
+                    };
+                    match entry {
+                        Some((_, uuid, entry)) => (200, json!({ uuid: entry }).to_string()),
+                        None => not_found(),
+                    }
+                }
+                _ => not_found(),
+            }
+        }
+
+        /// Integrates `proposed` in the log, returning the response to its upload.
+        fn integrate(&mut self, proposed: &Value) -> (u16, String) {
+            let body = canonicalize(&Self::body(proposed));
+            if let Some((_, uuid, _)) = self.entries.iter().find(|(other, ..)| *other == body) {
+                let message = format!(
+                    "An equivalent entry already exists in the transparency log with UUID {uuid}"
+                );
+                return (409, json!({"code": 409, "message": message}).to_string());
+            }
+
+            let leaf_hash = merkle::hash_leaf(&body);
+            let uuid = format!("24296fb24b8ad77a{}", hex::encode(leaf_hash));
+            let log_id = hex::encode(self.log_id);
+            let log_index = self.entries.len();
+            let integrated_time = chrono::Utc::now().timestamp();
+
+            let set = self
+                .signer
+                .sign(&canonicalize(&json!({
+                    "body": BASE64_STD_ENGINE.encode(&body),
+                    "integratedTime": integrated_time,
+                    "logID": log_id,
+                    "logIndex": log_index,
+                })))
+                .expect("failed to sign SET");
+            let checkpoint = sign(
+                &self.signer,
+                &self.log_id,
+                &format!("origin\n1\n{}\n", BASE64_STD_ENGINE.encode(leaf_hash)),
+            );
+            let entry = json!({
+                "body": BASE64_STD_ENGINE.encode(&body),
+                "integratedTime": integrated_time,
+                "logID": log_id,
+                "logIndex": log_index,
+                "verification": {
+                    "inclusionProof": {
+                        "checkpoint": checkpoint,
+                        "hashes": [],
+                        "logIndex": 0,
+                        "rootHash": hex::encode(leaf_hash),
+                        "treeSize": 1,
+                    },
+                    "signedEntryTimestamp": BASE64_STD_ENGINE.encode(set),
+                },
+            });
+
+            self.entries.push((body, uuid.clone(), entry.clone()));
+            (201, json!({ uuid: entry }).to_string())
+        }
+
+        /// Returns the body Rekor stores for `proposed`. Like Rekor, `dsse` entries are stored
+        /// with the digests of their envelope and payload instead of the envelope itself.
+        fn body(proposed: &Value) -> Value {
+            let spec = &proposed["spec"];
+            let spec = match proposed["kind"].as_str() {
+                Some("dsse") => {
+                    let content = &spec["proposedContent"];
+                    let envelope = content["envelope"].as_str().unwrap_or_default();
+                    let parsed: Value = serde_json::from_str(envelope).unwrap_or_default();
+                    let payload = BASE64_STD_ENGINE
+                        .decode(parsed["payload"].as_str().unwrap_or_default())
+                        .unwrap_or_default();
+                    let hash = |data: &[u8]| json!({"algorithm": "sha256", "value": hex::encode(Sha256::digest(data))});
+                    let signatures = parsed["signatures"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .zip(content["verifiers"].as_array().into_iter().flatten())
+                        .map(|(signature, verifier)| {
+                            json!({"signature": signature["sig"], "verifier": verifier})
+                        })
+                        .collect::<Vec<_>>();
+
+                    json!({
+                        "envelopeHash": hash(envelope.as_bytes()),
+                        "payloadHash": hash(&payload),
+                        "signatures": signatures,
+                    })
+                }
+                _ => spec.clone(),
+            };
+
+            json!({
+                "apiVersion": proposed["apiVersion"],
+                "kind": proposed["kind"],
+                "spec": spec,
+            })
+        }
+    }
+
+    fn canonicalize(value: &Value) -> Vec<u8> {
+        let mut value = json_syntax::to_value(value).expect("failed to convert JSON value");
+        value.canonicalize();
+        value.compact_print().to_string().into_bytes()
+    }
+
+    #[test]
+    fn rekor_error_from_response() {
+        let response = |content: &str| {
+            ApiError::<CreateLogEntryError>::ResponseError(ResponseContent {
+                status: StatusCode::CONFLICT,
+                content: content.to_owned(),
+                entity: None,
+            })
+        };
+
+        let err = RekorError::from(response(
+            r#"{"code":409,"message":"An equivalent entry already exists in the transparency log"}"#,
+        ));
+        assert_eq!(err.status(), Some(StatusCode::CONFLICT));
+        assert!(matches!(
+            err,
+            RekorError::Response { message, .. }
+                if message == "An equivalent entry already exists in the transparency log"
+        ));
+
+        let err = RekorError::from(response("upstream unavailable\n"));
+        assert!(matches!(
+            err,
+            RekorError::Response { message, .. } if message == "upstream unavailable"
+        ));
+    }
+
+    #[tokio::test]
+    async fn entry_by_index_out_of_range() {
+        let client = RekorClient::default();
+
+        assert!(matches!(
+            client.entry_by_index(u64::MAX).await,
+            Err(RekorError::LogIndexOutOfRange(u64::MAX))
+        ));
+    }
+
+    #[tokio::test]
+    async fn fetched_entries_are_the_requested_ones() {
+        let rekor = FakeRekor::new();
+        let client = RekorClient::new(rekor.configuration());
+        let digest = |value: &str| {
+            hashedrekord::Hash::new(hashedrekord::AlgorithmKind::sha256, value.repeat(64))
+        };
+        let entry = client
+            .upload_hashedrekord(b"signature", "public key", digest("a"))
+            .await
+            .expect("failed to upload entry");
+        let other = client
+            .upload_hashedrekord(b"signature", "public key", digest("b"))
+            .await
+            .expect("failed to upload entry");
+
+        // A log answering every request with the first entry.
+        let response = reqwest::get(format!("{}/api/v1/log/entries?logIndex=0", rekor.url))
+            .await
+            .and_then(|response| response.error_for_status())
+            .expect("failed to fetch entry")
+            .text()
+            .await
+            .expect("failed to read entry");
+        let client = RekorClient::new(Configuration {
+            base_path: serve(move |_| (200, response.clone())),
+            ..Default::default()
+        });
+
+        assert!(client.entry_by_index(0).await.is_ok());
+        assert!(matches!(
+            client.entry_by_index(1).await,
+            Err(RekorError::EntryMismatch { .. })
+        ));
+
+        assert!(client.entry_by_uuid(&entry.uuid).await.is_ok());
+        assert!(client.entry_by_uuid(&entry.uuid[16..]).await.is_ok());
+        assert!(matches!(
+            client.entry_by_uuid(&other.uuid).await,
+            Err(RekorError::EntryMismatch { .. })
+        ));
+    }
+}
@@ -25,6 +25,9 @@
 //! Sigstore's [security process](https://github.com/sigstore/community/blob/main/SECURITY.md).
 //!
 //! # How to use this crate
+//! [`client::RekorClient`] offers typed methods for the most common API calls. The generated
+//! functions of the [`apis`] module remain available for the others.
+//!
 //! The examples folder contains code that shows users how to make API calls.
 //! It also provides a clean interface with step-by-step instructions that other developers can copy and paste.
 //!
@@ -87,5 +90,7 @@
 //!
 
 pub mod apis;
+pub mod client;
 pub mod models;
+pub mod witness;
 type TreeSize = i64;
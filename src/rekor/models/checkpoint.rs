@@ -0,0 +1,451 @@
+//
+// Copyright 2024 The Sigstore Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checkpoints published by Rekor, in [signed note] format.
+//!
+//! A checkpoint commits to the state of the log (its size and Merkle tree root hash) at a given
+//! point in time; see the [checkpoint format] for details.
+//!
+//! [signed note]: https://github.com/C2SP/C2SP/blob/main/signed-note.md
+//! [checkpoint format]: https://github.com/transparency-dev/formats/blob/main/log/README.md
+
+use std::fmt::Write as _;
+use std::str::FromStr;
+
+use base64::{engine::general_purpose::STANDARD as base64, Engine as _};
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+use crate::crypto::keyring::{Keyring, KeyringError};
+use crate::crypto::merkle::MerkleHash;
+
+/// Prefix of every signature line of a signed note: an em dash followed by a space.
+const SIGNATURE_PREFIX: &str = "\u{2014} ";
+
+#[derive(Error, Debug)]
+pub enum CheckpointError {
+    #[error("malformed checkpoint: {0}")]
+    Malformed(&'static str),
+
+    #[error("checkpoint has no signature from log {0}")]
+    SignatureNotFound(String),
+
+    #[error("checkpoint signature verification failed")]
+    VerificationFailed(#[source] KeyringError),
+
+    #[error("checkpoint root hash {checkpoint} does not match proof root hash {proof}")]
+    RootHashMismatch { checkpoint: String, proof: String },
+
+    #[error("checkpoint tree size {checkpoint} does not match proof tree size {proof}")]
+    TreeSizeMismatch { checkpoint: u64, proof: u64 },
+}
+
+/// The body of a checkpoint: the part of the note covered by its signatures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckpointNote {
+    /// Unique identifier of the log that produced the checkpoint.
+    pub origin: String,
+    /// Number of entries in the log.
+    pub size: u64,
+    /// Merkle tree root hash of the log at `size`.
+    pub hash: MerkleHash,
+    /// Optional extension lines, e.g. the timestamp Rekor appends to its checkpoints.
+    pub other_content: Vec<String>,
+}
+
+impl CheckpointNote {
+    /// Serializes the note back into the exact text that was signed.
+    pub fn marshal(&self) -> String {
+        let mut note = format!(
+            "{}\n{}\n{}\n",
+            self.origin,
+            self.size,
+            base64.encode(self.hash)
+        );
+        for line in &self.other_content {
+            let _ = writeln!(note, "{line}");
+        }
+        note
+    }
+}
+
+impl FromStr for CheckpointNote {
+    type Err = CheckpointError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let body = s
+            .strip_suffix('\n')
+            .ok_or(CheckpointError::Malformed("note must end with a newline"))?;
+        let mut lines = body.split('\n');
+
+        let origin = lines
+            .next()
+            .filter(|origin| !origin.is_empty())
+            .ok_or(CheckpointError::Malformed("missing origin"))?
+            .to_owned();
+        let size = lines
+            .next()
+            .and_then(|size| size.parse().ok())
+            .ok_or(CheckpointError::Malformed("missing or invalid tree size"))?;
+        let hash = lines
+            .next()
+            .and_then(|hash| base64.decode(hash).ok())
+            .and_then(|hash| hash.try_into().ok())
+            .ok_or(CheckpointError::Malformed("missing or invalid root hash"))?;
+
+        let other_content = lines
+            .map(|line| match line {
+                "" => Err(CheckpointError::Malformed("empty line in note")),
+                line => Ok(line.to_owned()),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(CheckpointNote {
+            origin,
+            size,
+            hash,
+            other_content,
+        })
+    }
+}
+
+/// A signature line of a signed note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckpointSignature {
+    /// Name of the signer, usually the log's origin.
+    pub name: String,
+    /// The first four bytes of the signer's key ID.
+    pub key_hint: [u8; 4],
+    /// The signature over the marshaled note.
+    pub raw: Vec<u8>,
+}
+
+impl FromStr for CheckpointSignature {
+    type Err = CheckpointError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, signature) = s
+            .strip_prefix(SIGNATURE_PREFIX)
+            .and_then(|line| line.split_once(' '))
+            .filter(|(name, _)| !name.is_empty())
+            .ok_or(CheckpointError::Malformed("invalid signature line"))?;
+
+        let signature = base64
+            .decode(signature)
+            .or(Err(CheckpointError::Malformed("signature is not base64")))?;
+        if signature.len() <= 4 {
+            return Err(CheckpointError::Malformed("signature too short"));
+        }
+        let (key_hint, raw) = signature.split_at(4);
+
+        Ok(CheckpointSignature {
+            name: name.to_owned(),
+            key_hint: key_hint.try_into().expect("split at 4 bytes"),
+            raw: raw.to_owned(),
+        })
+    }
+}
+
+/// A checkpoint and the signatures over it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedCheckpoint {
+    pub note: CheckpointNote,
+    pub signatures: Vec<CheckpointSignature>,
+}
+
+impl FromStr for SignedCheckpoint {
+    type Err = CheckpointError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The note and its signatures are separated by a blank line; the note keeps its final
+        // newline, which is covered by the signatures.
+        let split = s
+            .find("\n\n")
+            .ok_or(CheckpointError::Malformed("missing signature block"))?;
+        let (note, signatures) = (&s[..=split], &s[split + 2..]);
+
+        let note = note.parse()?;
+        let signatures = signatures
+            .strip_suffix('\n')
+            .ok_or(CheckpointError::Malformed(
+                "signatures must end with a newline",
+            ))?
+            .split('\n')
+            .map(CheckpointSignature::from_str)
+            .collect::<Result<_, _>>()?;
+
+        Ok(SignedCheckpoint { note, signatures })
+    }
+}
+
+impl SignedCheckpoint {
+    /// Serializes the checkpoint and its signatures back into signed note format.
+    pub fn marshal(&self) -> String {
+        let mut checkpoint = self.note.marshal();
+        checkpoint.push('\n');
+        for signature in &self.signatures {
+            let mut raw = signature.key_hint.to_vec();
+            raw.extend(&signature.raw);
+            let _ = writeln!(
+                checkpoint,
+                "{SIGNATURE_PREFIX}{} {}",
+                signature.name,
+                base64.encode(raw)
+            );
+        }
+        checkpoint
+    }
+
+    /// Verifies that the checkpoint is signed by the log identified by `log_id`, whose key must
+    /// be held in `keyring`.
+    pub fn verify_signature(
+        &self,
+        keyring: &Keyring,
+        log_id: &[u8],
+    ) -> Result<(), CheckpointError> {
+        keyring
+            .verify(
+                log_id,
+                self.signature(log_id)?,
+                self.note.marshal().as_bytes(),
+            )
+            .map_err(CheckpointError::VerificationFailed)
+    }
+
+    /// Verifies that the checkpoint is signed by the log identified by `log_id`, whose key must
+    /// be held in `keyring` and must have been valid at `time`.
+    pub fn verify_signature_at(
+        &self,
+        keyring: &Keyring,
+        log_id: &[u8],
+        time: DateTime<Utc>,
+    ) -> Result<(), CheckpointError> {
+        keyring
+            .verify_at(
+                log_id,
+                self.signature(log_id)?,
+                self.note.marshal().as_bytes(),
+                time,
+            )
+            .map_err(CheckpointError::VerificationFailed)
+    }
+
+    /// Returns the raw signature of the log identified by `log_id`.
+    fn signature(&self, log_id: &[u8]) -> Result<&[u8], CheckpointError> {
+        let key_hint = log_id.get(..4);
+        self.signatures
+            .iter()
+            .find(|sig| Some(&sig.key_hint[..]) == key_hint)
+            .map(|sig| sig.raw.as_slice())
+            .ok_or_else(|| CheckpointError::SignatureNotFound(hex::encode(log_id)))
+    }
+
+    /// Checks that the checkpoint commits to the tree described by an inclusion proof.
+    pub fn is_valid_for_proof(
+        &self,
+        root_hash: &[u8],
+        tree_size: u64,
+    ) -> Result<(), CheckpointError> {
+        if self.note.size != tree_size {
+            return Err(CheckpointError::TreeSizeMismatch {
+                checkpoint: self.note.size,
+                proof: tree_size,
+            });
+        }
+        if self.note.hash[..] != root_hash[..] {
+            return Err(CheckpointError::RootHashMismatch {
+                checkpoint: hex::encode(self.note.hash),
+                proof: hex::encode(root_hash),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::crypto::{keyring::key_id, SigStoreSigner, SigningScheme};
+    use crate::trust::{HashAlgorithm, TransparencyLogKey, ValidityPeriod};
+
+    const NOTE: &str = "rekor.sigstore.dev - 2605736670972794746\n\
+        21428036\n\
+        rMj3G9LfM9C6Xt0qpV3pHbM2q5lPvKjS0mFmxvR6sdA=\n\
+        Timestamp: 1689748607742585419\n";
+
+    pub(crate) fn log_signer() -> (SigStoreSigner, Keyring, [u8; 32]) {
+        let signer = SigningScheme::ECDSA_P256_SHA256_ASN1
+            .create_signer()
+            .expect("failed to create signer");
+        let public_key = signer
+            .to_sigstore_keypair()
+            .and_then(|k| k.public_key_to_der())
+            .expect("failed to export public key");
+        let keyring = Keyring::new([public_key.as_slice()]).expect("failed to build keyring");
+
+        (signer, keyring, key_id(&public_key))
+    }
+
+    pub(crate) fn sign(signer: &SigStoreSigner, log_id: &[u8], note: &str) -> String {
+        let mut signature = log_id[..4].to_vec();
+        signature.extend(signer.sign(note.as_bytes()).expect("failed to sign"));
+
+        format!(
+            "{note}\n\u{2014} rekor.sigstore.dev {}\n",
+            base64.encode(signature)
+        )
+    }
+
+    #[test]
+    fn parse_checkpoint() {
+        let checkpoint: SignedCheckpoint = format!(
+            "{NOTE}\n\u{2014} rekor.sigstore.dev {}\n",
+            base64.encode([1u8, 2, 3, 4, 5, 6])
+        )
+        .parse()
+        .expect("failed to parse checkpoint");
+
+        assert_eq!(
+            checkpoint.note.origin,
+            "rekor.sigstore.dev - 2605736670972794746"
+        );
+        assert_eq!(checkpoint.note.size, 21428036);
+        assert_eq!(
+            checkpoint.note.other_content,
+            vec!["Timestamp: 1689748607742585419"]
+        );
+        assert_eq!(checkpoint.note.marshal(), NOTE);
+        assert_eq!(checkpoint.marshal().parse().ok(), Some(checkpoint.clone()));
+        assert_eq!(
+            checkpoint.signatures,
+            vec![CheckpointSignature {
+                name: "rekor.sigstore.dev".into(),
+                key_hint: [1, 2, 3, 4],
+                raw: vec![5, 6],
+            }]
+        );
+    }
+
+    #[rstest]
+    #[case::no_signatures(NOTE)]
+    #[case::missing_origin(
+        "\n21428036\nrMj3G9LfM9C6Xt0qpV3pHbM2q5lPvKjS0mFmxvR6sdA=\n\n\u{2014} a AQIDBAU=\n"
+    )]
+    #[case::invalid_size(
+        "origin\nbig\nrMj3G9LfM9C6Xt0qpV3pHbM2q5lPvKjS0mFmxvR6sdA=\n\n\u{2014} a AQIDBAU=\n"
+    )]
+    #[case::short_hash("origin\n1\nAQID\n\n\u{2014} a AQIDBAU=\n")]
+    #[case::missing_hash("origin\n1\n\n\u{2014} a AQIDBAU=\n")]
+    #[case::bad_signature_prefix(
+        "origin\n1\nrMj3G9LfM9C6Xt0qpV3pHbM2q5lPvKjS0mFmxvR6sdA=\n\n- a AQIDBAU=\n"
+    )]
+    #[case::short_signature(
+        "origin\n1\nrMj3G9LfM9C6Xt0qpV3pHbM2q5lPvKjS0mFmxvR6sdA=\n\n\u{2014} a AQID\n"
+    )]
+    #[case::no_trailing_newline(
+        "origin\n1\nrMj3G9LfM9C6Xt0qpV3pHbM2q5lPvKjS0mFmxvR6sdA=\n\n\u{2014} a AQIDBAU="
+    )]
+    fn parse_malformed_checkpoint(#[case] checkpoint: &str) {
+        assert!(matches!(
+            checkpoint.parse::<SignedCheckpoint>(),
+            Err(CheckpointError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn verify_checkpoint() {
+        let (signer, keyring, log_id) = log_signer();
+        let checkpoint: SignedCheckpoint = sign(&signer, &log_id, NOTE)
+            .parse()
+            .expect("failed to parse checkpoint");
+
+        assert!(checkpoint.verify_signature(&keyring, &log_id).is_ok());
+
+        let (_, other_keyring, other_log_id) = log_signer();
+        assert!(matches!(
+            checkpoint.verify_signature(&other_keyring, &other_log_id),
+            Err(CheckpointError::SignatureNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn verify_checkpoint_key_validity() {
+        let (signer, _, log_id) = log_signer();
+        let public_key = signer
+            .to_sigstore_keypair()
+            .and_then(|k| k.public_key_to_der())
+            .expect("failed to export public key");
+        let at = |secs| DateTime::from_timestamp(secs, 0).expect("invalid timestamp");
+        let keyring = Keyring::from_log_keys([TransparencyLogKey {
+            log_id: log_id.to_vec(),
+            hash_algorithm: HashAlgorithm::Sha2_256,
+            public_key: &public_key,
+            valid_for: ValidityPeriod {
+                start: Some(at(100)),
+                end: Some(at(200)),
+            },
+        }])
+        .expect("failed to build keyring");
+        let checkpoint: SignedCheckpoint = sign(&signer, &log_id, NOTE)
+            .parse()
+            .expect("failed to parse checkpoint");
+
+        assert!(checkpoint
+            .verify_signature_at(&keyring, &log_id, at(150))
+            .is_ok());
+        assert!(matches!(
+            checkpoint.verify_signature_at(&keyring, &log_id, at(250)),
+            Err(CheckpointError::VerificationFailed(
+                KeyringError::KeyNotValid(..)
+            ))
+        ));
+    }
+
+    #[test]
+    fn verify_tampered_checkpoint() {
+        let (signer, keyring, log_id) = log_signer();
+        let mut checkpoint: SignedCheckpoint = sign(&signer, &log_id, NOTE)
+            .parse()
+            .expect("failed to parse checkpoint");
+        checkpoint.note.size += 1;
+
+        assert!(matches!(
+            checkpoint.verify_signature(&keyring, &log_id),
+            Err(CheckpointError::VerificationFailed(_))
+        ));
+    }
+
+    #[test]
+    fn checkpoint_valid_for_proof() {
+        let checkpoint = SignedCheckpoint {
+            note: NOTE.parse().expect("failed to parse note"),
+            signatures: vec![],
+        };
+        let hash = checkpoint.note.hash;
+
+        assert!(checkpoint.is_valid_for_proof(&hash, 21428036).is_ok());
+        assert!(matches!(
+            checkpoint.is_valid_for_proof(&hash, 21428035),
+            Err(CheckpointError::TreeSizeMismatch { .. })
+        ));
+        assert!(matches!(
+            checkpoint.is_valid_for_proof(&[0; 32], 21428036),
+            Err(CheckpointError::RootHashMismatch { .. })
+        ));
+    }
+}
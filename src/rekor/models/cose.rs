@@ -0,0 +1,80 @@
+//
+// Copyright 2024 The Sigstore Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+
+use super::hashedrekord::Hash;
+
+/// Cose : COSE object
+
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Cose {
+    #[serde(rename = "kind")]
+    pub kind: String,
+    #[serde(rename = "apiVersion")]
+    pub api_version: String,
+    #[serde(rename = "spec")]
+    pub spec: Spec,
+}
+
+impl Cose {
+    /// COSE object
+    pub fn new(kind: String, api_version: String, spec: Spec) -> Cose {
+        Cose {
+            kind,
+            api_version,
+            spec,
+        }
+    }
+}
+
+/// Stores the contents of a `cose` v0.0.1 entry.
+///
+/// Proposed entries hold the `COSE_Sign1` message. Rekor does not store the message: the entries
+/// integrated in the log hold its digests in [`Data`] instead.
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Spec {
+    /// The base64-encoded `COSE_Sign1` message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// The base64-encoded PEM public key or certificate verifying the message
+    pub public_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Data>,
+}
+
+impl Spec {
+    pub fn new(message: String, public_key: String) -> Spec {
+        Spec {
+            message: Some(message),
+            public_key,
+            data: None,
+        }
+    }
+}
+
+/// Stores the digests of the message and of its payload, and the additional authenticated data
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Data {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub envelope_hash: Option<Hash>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload_hash: Option<Hash>,
+    /// The base64-encoded additional authenticated data of the message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aad: Option<String>,
+}
@@ -0,0 +1,106 @@
+//
+// Copyright 2024 The Sigstore Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+
+use super::hashedrekord::Hash;
+
+/// Dsse : Dsse object
+
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Dsse {
+    #[serde(rename = "kind")]
+    pub kind: String,
+    #[serde(rename = "apiVersion")]
+    pub api_version: String,
+    #[serde(rename = "spec")]
+    pub spec: Spec,
+}
+
+impl Dsse {
+    /// Dsse object
+    pub fn new(kind: String, api_version: String, spec: Spec) -> Dsse {
+        Dsse {
+            kind,
+            api_version,
+            spec,
+        }
+    }
+}
+
+/// Stores the contents of a `dsse` v0.0.1 entry.
+///
+/// Proposed entries only hold the [`ProposedContent`]. Rekor does not store the envelope: the
+/// entries integrated in the log hold the digests of the envelope and of its payload, and the
+/// envelope's signatures instead.
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Spec {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proposed_content: Option<ProposedContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub envelope_hash: Option<Hash>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload_hash: Option<Hash>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signatures: Vec<Signature>,
+}
+
+impl Spec {
+    pub fn new(proposed_content: ProposedContent) -> Spec {
+        Spec {
+            proposed_content: Some(proposed_content),
+            ..Default::default()
+        }
+    }
+}
+
+/// Stores the DSSE envelope to log, and the keys or certificates verifying its signatures
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposedContent {
+    /// The envelope, in its JSON serialization
+    pub envelope: String,
+    /// The base64-encoded PEM public keys or certificates, one per signature of the envelope
+    pub verifiers: Vec<String>,
+}
+
+impl ProposedContent {
+    pub fn new(envelope: String, verifiers: Vec<String>) -> ProposedContent {
+        ProposedContent {
+            envelope,
+            verifiers,
+        }
+    }
+}
+
+/// Stores a signature of the envelope and the public key or certificate verifying it
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Signature {
+    /// The base64-encoded signature
+    pub signature: String,
+    /// The base64-encoded PEM public key or certificate
+    pub verifier: String,
+}
+
+impl Signature {
+    pub fn new(signature: String, verifier: String) -> Signature {
+        Signature {
+            signature,
+            verifier,
+        }
+    }
+}
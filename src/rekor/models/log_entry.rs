@@ -13,23 +13,26 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::errors::SigstoreError;
+use crate::crypto::keyring::{Keyring, KeyringError};
+use crate::crypto::merkle::{self, MerkleProofError};
+use crate::rekor::models::checkpoint::{CheckpointError, SignedCheckpoint};
 use crate::rekor::TreeSize;
 use base64::{engine::general_purpose::STANDARD as BASE64_STD_ENGINE, Engine as _};
+use json_syntax::Print;
 
-use serde::{Deserialize, Serialize};
-use serde_json::{json, Error, Value};
-use std::collections::HashMap;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Map, Value};
 use std::str::FromStr;
+use thiserror::Error;
 
 use super::{
-    AlpineAllOf, HashedrekordAllOf, HelmAllOf, IntotoAllOf, JarAllOf, RekordAllOf, Rfc3161AllOf,
-    RpmAllOf, TufAllOf,
+    AlpineAllOf, CoseAllOf, DsseAllOf, HashedrekordAllOf, HelmAllOf, IntotoAllOf, JarAllOf,
+    RekordAllOf, Rfc3161AllOf, RpmAllOf, TufAllOf,
 };
 
 /// Stores the response returned by Rekor after making a new entry
 #[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
-#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
 pub struct LogEntry {
     pub uuid: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -42,24 +45,196 @@ pub struct LogEntry {
 }
 
 impl FromStr for LogEntry {
-    type Err = Error;
+    type Err = LogEntryError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut log_entry_map = serde_json::from_str::<HashMap<&str, Value>>(s)?;
-        log_entry_map.entry("body").and_modify(|body| {
-            let decoded_body = serde_json::to_value(
-                decode_body(body.as_str().expect("Failed to parse Body"))
-                    .expect("Failed to decode Body"),
-            )
-            .expect("Serialization failed");
-            *body = json!(decoded_body);
+        let malformed = |err: serde_json::Error| LogEntryError::Malformed(err.to_string());
+        let mut log_entry_map = serde_json::from_str::<Map<String, Value>>(s).map_err(malformed)?;
+
+        // Rekor serves the body as base64-encoded JSON.
+        let body = match log_entry_map.get("body") {
+            Some(Value::String(body)) => decode_body(body)?,
+            Some(_) => return Err(LogEntryError::Malformed("body is not a string".into())),
+            None => return Err(LogEntryError::Malformed("body is missing".into())),
+        };
+        log_entry_map.insert(
+            "body".into(),
+            serde_json::to_value(body).map_err(malformed)?,
+        );
+
+        serde_json::from_value(Value::Object(log_entry_map)).map_err(malformed)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum LogEntryError {
+    #[error("log entry is malformed: {0}")]
+    Malformed(String),
+
+    #[error("log entry body could not be canonicalized")]
+    BodyMalformed,
+
+    #[error("log ID {0} is malformed")]
+    LogIdMalformed(String),
+
+    #[error("signed entry timestamp is malformed")]
+    SignedEntryTimestampMalformed,
+
+    #[error("signed entry timestamp verification failed")]
+    SignedEntryTimestamp(#[source] KeyringError),
+
+    #[error("log entry is missing its inclusion proof")]
+    InclusionProofMissing,
+
+    #[error("inclusion proof is malformed")]
+    InclusionProofMalformed,
+
+    #[error("inclusion proof verification failed")]
+    InclusionProof(#[source] MerkleProofError),
+
+    #[error("checkpoint verification failed")]
+    Checkpoint(#[source] CheckpointError),
+
+    #[error("UUID {uuid} does not match the leaf hash {leaf_hash} of the log entry")]
+    UuidMismatch { uuid: String, leaf_hash: String },
+}
+
+impl LogEntry {
+    /// Returns the canonical JSON serialization of the entry's body, as integrated in the log.
+    pub fn canonicalized_body(&self) -> Result<Vec<u8>, LogEntryError> {
+        let mut body = json_syntax::to_value(&self.body).or(Err(LogEntryError::BodyMalformed))?;
+        body.canonicalize();
+
+        Ok(body.compact_print().to_string().into_bytes())
+    }
+
+    /// Verifies the entry against the keys of its transparency log, held in `keyring`.
+    ///
+    /// The entry's Signed Entry Timestamp (SET) must be signed by the log, its inclusion proof
+    /// must commit to its body (and be consistent with the proof's signed checkpoint, if any),
+    /// and its UUID must be the Merkle leaf hash of its body.
+    ///
+    /// Older entries carry no checkpoint. Their inclusion proof is then only checked against
+    /// the unsigned root hash it carries, which proves nothing about the log's actual tree:
+    /// such entries are authenticated by their SET alone.
+    pub fn verify(&self, keyring: &Keyring) -> Result<(), LogEntryError> {
+        let body = self.canonicalized_body()?;
+        let log_id = hex::decode(&self.log_i_d)
+            .or(Err(LogEntryError::LogIdMalformed(self.log_i_d.clone())))?;
+
+        self.verify_signed_entry_timestamp(keyring, &body, &log_id)?;
+
+        let leaf_hash = merkle::hash_leaf(&body);
+        self.verify_inclusion_proof(keyring, &leaf_hash, &log_id)?;
+
+        let leaf_hash = hex::encode(leaf_hash);
+        if uuid_leaf_hash(&self.uuid) != Some(leaf_hash.clone()) {
+            return Err(LogEntryError::UuidMismatch {
+                uuid: self.uuid.clone(),
+                leaf_hash,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn verify_signed_entry_timestamp(
+        &self,
+        keyring: &Keyring,
+        body: &[u8],
+        log_id: &[u8],
+    ) -> Result<(), LogEntryError> {
+        let signature = BASE64_STD_ENGINE
+            .decode(&self.verification.signed_entry_timestamp)
+            .or(Err(LogEntryError::SignedEntryTimestampMalformed))?;
+        let payload = json!({
+            "body": BASE64_STD_ENGINE.encode(body),
+            "integratedTime": self.integrated_time,
+            "logIndex": self.log_index,
+            "logID": self.log_i_d,
         });
-        let log_entry_str = serde_json::to_string(&log_entry_map)?;
-        Ok(serde_json::from_str::<LogEntry>(&log_entry_str).expect("Serialization failed"))
+        let payload = {
+            let mut payload = json_syntax::to_value(payload)
+                .or(Err(LogEntryError::SignedEntryTimestampMalformed))?;
+            payload.canonicalize();
+            payload.compact_print().to_string().into_bytes()
+        };
+
+        // The log key must have been valid when the entry was integrated.
+        let integrated_time = chrono::DateTime::from_timestamp(self.integrated_time, 0)
+            .ok_or(LogEntryError::SignedEntryTimestampMalformed)?;
+
+        keyring
+            .verify_at(log_id, &signature, &payload, integrated_time)
+            .map_err(LogEntryError::SignedEntryTimestamp)
     }
+
+    fn verify_inclusion_proof(
+        &self,
+        keyring: &Keyring,
+        leaf_hash: &merkle::MerkleHash,
+        log_id: &[u8],
+    ) -> Result<(), LogEntryError> {
+        let proof = self
+            .verification
+            .inclusion_proof
+            .as_ref()
+            .ok_or(LogEntryError::InclusionProofMissing)?;
+
+        let (Ok(index), Ok(tree_size)) = (proof.log_index.try_into(), proof.tree_size.try_into())
+        else {
+            return Err(LogEntryError::InclusionProofMalformed);
+        };
+        let root_hash =
+            hex::decode(&proof.root_hash).or(Err(LogEntryError::InclusionProofMalformed))?;
+        let hashes = proof
+            .hashes
+            .iter()
+            .map(hex::decode)
+            .collect::<Result<Vec<_>, _>>()
+            .or(Err(LogEntryError::InclusionProofMalformed))?;
+
+        merkle::verify_inclusion(index, tree_size, leaf_hash, &hashes, &root_hash)
+            .map_err(LogEntryError::InclusionProof)?;
+
+        // Older log entries do not carry a checkpoint. Without one, the root hash is not signed
+        // by the log, and the proof does not authenticate the entry: the SET does.
+        if proof.checkpoint.is_empty() {
+            return Ok(());
+        }
+        let checkpoint: SignedCheckpoint = proof
+            .checkpoint
+            .parse()
+            .map_err(LogEntryError::Checkpoint)?;
+        // The log key must have been valid when the entry was integrated.
+        let integrated_time = chrono::DateTime::from_timestamp(self.integrated_time, 0)
+            .ok_or(LogEntryError::InclusionProofMalformed)?;
+        checkpoint
+            .verify_signature_at(keyring, log_id, integrated_time)
+            .and_then(|_| checkpoint.is_valid_for_proof(&root_hash, tree_size))
+            .map_err(LogEntryError::Checkpoint)
+    }
+}
+
+/// Returns the lowercase leaf hash identified by an entry UUID.
+///
+/// UUIDs are the hex-encoded leaf hash, prefixed by a 16 characters tree ID in sharded logs.
+pub(crate) fn uuid_leaf_hash(uuid: &str) -> Option<String> {
+    let leaf_hash = match uuid.len() {
+        64 => Some(uuid),
+        80 => uuid.get(16..),
+        _ => None,
+    };
+
+    leaf_hash.map(str::to_ascii_lowercase)
 }
 
+/// The body of a log entry, tagged by its kind.
+///
+/// Bodies this crate cannot fully decode, such as those of kinds or versions introduced after
+/// this release, are kept as [`Body::Unknown`]: they serialize back to their original JSON, so
+/// the entries holding them can still be hashed and verified.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(tag = "kind")]
+#[serde(remote = "Self", tag = "kind")]
 #[allow(non_camel_case_types)]
 pub enum Body {
     alpine(AlpineAllOf),
@@ -69,8 +244,54 @@ pub enum Body {
     rpm(RpmAllOf),
     tuf(TufAllOf),
     intoto(IntotoAllOf),
+    cose(CoseAllOf),
+    dsse(DsseAllOf),
     hashedrekord(HashedrekordAllOf),
     rekord(RekordAllOf),
+    #[serde(skip)]
+    Unknown {
+        kind: String,
+        api_version: String,
+        /// The body, as served by Rekor
+        raw: Value,
+    },
+}
+
+impl Serialize for Body {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Body::Unknown { raw, .. } => raw.serialize(serializer),
+            body => Body::serialize(body, serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Body {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Header {
+            kind: String,
+            api_version: String,
+        }
+
+        let raw = Value::deserialize(deserializer)?;
+        // Typed bodies ignore the fields they do not know of: only keep those holding the whole
+        // body, as its hash commits to every field.
+        if let Ok(body) = Body::deserialize(&raw) {
+            if Body::serialize(&body, serde_json::value::Serializer).is_ok_and(|value| value == raw)
+            {
+                return Ok(body);
+            }
+        }
+
+        let Header { kind, api_version } = Header::deserialize(&raw).map_err(D::Error::custom)?;
+        Ok(Body::Unknown {
+            kind,
+            api_version,
+            raw,
+        })
+    }
 }
 
 impl Default for Body {
@@ -79,9 +300,12 @@ impl Default for Body {
     }
 }
 
-fn decode_body(s: &str) -> Result<Body, SigstoreError> {
-    let decoded = BASE64_STD_ENGINE.decode(s)?;
-    serde_json::from_slice(&decoded).map_err(SigstoreError::from)
+fn decode_body(s: &str) -> Result<Body, LogEntryError> {
+    let decoded = BASE64_STD_ENGINE
+        .decode(s)
+        .map_err(|err| LogEntryError::Malformed(format!("body is not base64: {err}")))?;
+    serde_json::from_slice(&decoded)
+        .map_err(|err| LogEntryError::Malformed(format!("body is malformed: {err}")))
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -117,3 +341,259 @@ pub struct InclusionProof {
     /// [Signed Note format]: https://github.com/transparency-dev/formats/blob/main/log/README.md
     pub checkpoint: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::crypto::SigStoreSigner;
+    use crate::errors::SigstoreError;
+    use crate::rekor::apis::entries_api::parse_response;
+    use crate::rekor::models::checkpoint::tests::{log_signer, sign};
+    use crate::rekor::models::dsse;
+
+    const BODY: &str = r#"{"apiVersion":"0.0.1","kind":"hashedrekord","spec":{"data":{"hash":{"algorithm":"sha256","value":"00"}}}}"#;
+
+    /// A response of Rekor's entries API holding a single entry, keyed by its UUID.
+    fn response(body: &str) -> String {
+        format!(
+            r#"{{"24296fb24b8ad77a":{{"body":"{}","integratedTime":1700000000,"logID":"c0d2","logIndex":7,"verification":{{"signedEntryTimestamp":"AAAA"}}}}}}"#,
+            BASE64_STD_ENGINE.encode(body)
+        )
+    }
+
+    #[test]
+    fn parse_log_entry() {
+        let entry: LogEntry = parse_response(response(BODY))
+            .parse()
+            .expect("failed to parse log entry");
+
+        assert_eq!(entry.uuid, "24296fb24b8ad77a");
+        assert_eq!(entry.log_index, 7);
+        assert!(matches!(entry.body, Body::hashedrekord(_)));
+        assert_eq!(
+            entry.canonicalized_body().ok(),
+            Some(BODY.as_bytes().to_vec())
+        );
+    }
+
+    #[rstest]
+    #[case::dsse(
+        r#"{"apiVersion":"0.0.1","kind":"dsse","spec":{"envelopeHash":{"algorithm":"sha256","value":"aa"},"payloadHash":{"algorithm":"sha256","value":"bb"},"signatures":[{"signature":"c2ln","verifier":"a2V5"}]}}"#
+    )]
+    #[case::cose(
+        r#"{"apiVersion":"0.0.1","kind":"cose","spec":{"data":{"envelopeHash":{"algorithm":"sha256","value":"aa"},"payloadHash":{"algorithm":"sha256","value":"bb"}},"publicKey":"a2V5"}}"#
+    )]
+    fn parse_log_entry_kind(#[case] body: &str) {
+        let entry: LogEntry = parse_response(response(body))
+            .parse()
+            .expect("failed to parse log entry");
+
+        match &entry.body {
+            Body::dsse(dsse) => assert_eq!(
+                dsse.spec.signatures,
+                [dsse::Signature::new("c2ln".into(), "a2V5".into())]
+            ),
+            Body::cose(cose) => assert_eq!(cose.spec.public_key, "a2V5"),
+            body => panic!("unexpected body {body:?}"),
+        }
+        assert_eq!(
+            entry.canonicalized_body().ok(),
+            Some(body.as_bytes().to_vec())
+        );
+    }
+
+    #[rstest]
+    #[case::unknown_kind(
+        r#"{"apiVersion":"0.0.1","kind":"unknown","spec":{"data":{"hash":{"algorithm":"sha256","value":"00"}}}}"#,
+        "unknown",
+        "0.0.1"
+    )]
+    #[case::unknown_version(
+        r#"{"apiVersion":"0.0.2","kind":"dsse","spec":{"envelope":{"payload":"cGF5bG9hZA=="}}}"#,
+        "dsse",
+        "0.0.2"
+    )]
+    fn parse_log_entry_unknown(#[case] body: &str, #[case] kind: &str, #[case] api_version: &str) {
+        // Fields added to the response by newer Rekor releases are ignored.
+        let content = response(body).replace(r#""logIndex":7"#, r#""logIndex":7,"new":{}"#);
+        let entry: LogEntry = parse_response(content)
+            .parse()
+            .expect("failed to parse log entry");
+
+        assert!(matches!(
+            &entry.body,
+            Body::Unknown { kind: k, api_version: v, .. } if k == kind && v == api_version
+        ));
+        assert_eq!(
+            entry.canonicalized_body().ok(),
+            Some(body.as_bytes().to_vec())
+        );
+    }
+
+    #[rstest]
+    #[case::empty("")]
+    #[case::null("null")]
+    #[case::array("[]")]
+    #[case::empty_object("{}")]
+    #[case::multibyte("{\"\u{e9}\u{e9}\u{e9}\":{}}")]
+    #[case::two_entries(r#"{"a":{"body":""},"b":{"body":""}}"#)]
+    #[case::entry_not_object(r#"{"24296fb24b8ad77a":"body"}"#)]
+    #[case::body_missing(r#"{"24296fb24b8ad77a":{"logIndex":7}}"#)]
+    #[case::body_number(r#"{"24296fb24b8ad77a":{"body":7}}"#)]
+    #[case::body_not_base64(r#"{"24296fb24b8ad77a":{"body":"not base64!"}}"#)]
+    #[case::fields_missing(&response(BODY).replace(r#""logIndex":7,"#, ""))]
+    #[case::field_mistyped(&response(BODY).replace(r#""logIndex":7"#, r#""logIndex":"7""#))]
+    #[case::body_not_json(&response("not json"))]
+    #[case::body_not_object(&response("[]"))]
+    #[case::body_kind_missing(&response(r#"{"apiVersion":"0.0.1","spec":{}}"#))]
+    fn parse_broken_log_entry(#[case] content: &str) {
+        assert!(matches!(
+            parse_response(content.to_owned()).parse::<LogEntry>(),
+            Err(LogEntryError::Malformed(_))
+        ));
+        assert!(matches!(
+            content.parse::<LogEntry>(),
+            Err(LogEntryError::Malformed(_))
+        ));
+
+        let parse = || -> crate::errors::Result<LogEntry> { Ok(content.parse()?) };
+        assert!(matches!(
+            parse(),
+            Err(SigstoreError::LogEntryError(err)) if matches!(*err, LogEntryError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn parse_truncated_log_entry() {
+        // Every strict prefix of a response is malformed, and must be rejected without panicking.
+        let content = response(BODY);
+        for (end, _) in content.char_indices() {
+            let truncated = &content[..end];
+            assert!(matches!(
+                parse_response(truncated.to_owned()).parse::<LogEntry>(),
+                Err(LogEntryError::Malformed(_))
+            ));
+        }
+    }
+
+    /// Builds an entry integrated as the single leaf of a log signed by `signer`.
+    fn signed_entry(signer: &SigStoreSigner, log_id: &[u8]) -> LogEntry {
+        signed_entry_with_body(
+            signer,
+            log_id,
+            Body::hashedrekord(HashedrekordAllOf {
+                api_version: "0.0.1".into(),
+                spec: json!({"data": {"hash": {"algorithm": "sha256", "value": "00"}}}),
+            }),
+        )
+    }
+
+    fn signed_entry_with_body(signer: &SigStoreSigner, log_id: &[u8], body: Body) -> LogEntry {
+        let mut entry = LogEntry {
+            body,
+            integrated_time: 1700000000,
+            log_i_d: hex::encode(log_id),
+            log_index: 7,
+            ..Default::default()
+        };
+        let body = entry
+            .canonicalized_body()
+            .expect("failed to canonicalize body");
+        let leaf_hash = merkle::hash_leaf(&body);
+
+        let payload = format!(
+            r#"{{"body":"{}","integratedTime":1700000000,"logID":"{}","logIndex":7}}"#,
+            BASE64_STD_ENGINE.encode(&body),
+            entry.log_i_d
+        );
+        let set = signer.sign(payload.as_bytes()).expect("failed to sign SET");
+        let checkpoint = sign(
+            signer,
+            log_id,
+            &format!("origin\n1\n{}\n", BASE64_STD_ENGINE.encode(leaf_hash)),
+        );
+
+        entry.uuid = format!("24296fb24b8ad77a{}", hex::encode(leaf_hash));
+        entry.verification = Verification {
+            inclusion_proof: Some(InclusionProof {
+                hashes: vec![],
+                log_index: 0,
+                root_hash: hex::encode(leaf_hash),
+                tree_size: 1,
+                checkpoint,
+            }),
+            signed_entry_timestamp: BASE64_STD_ENGINE.encode(set),
+        };
+        entry
+    }
+
+    #[test]
+    fn verify_log_entry() {
+        let (signer, keyring, log_id) = log_signer();
+        let entry = signed_entry(&signer, &log_id);
+        entry.verify(&keyring).expect("failed to verify log entry");
+
+        // UUIDs of unsharded logs are the bare leaf hash.
+        let entry = LogEntry {
+            uuid: entry.uuid[16..].to_owned(),
+            ..entry
+        };
+        entry.verify(&keyring).expect("failed to verify log entry");
+    }
+
+    #[test]
+    fn verify_log_entry_unknown() {
+        let (signer, keyring, log_id) = log_signer();
+        let body: Body = serde_json::from_str(
+            r#"{"apiVersion":"0.0.1","kind":"unknown","spec":{"key":"value"}}"#,
+        )
+        .expect("failed to parse body");
+        let entry = signed_entry_with_body(&signer, &log_id, body);
+
+        entry.verify(&keyring).expect("failed to verify log entry");
+    }
+
+    #[test]
+    fn verify_log_entry_tampered() {
+        let (signer, keyring, log_id) = log_signer();
+        let entry = signed_entry(&signer, &log_id);
+
+        let mut tampered = entry.clone();
+        tampered.integrated_time += 1;
+        assert!(matches!(
+            tampered.verify(&keyring),
+            Err(LogEntryError::SignedEntryTimestamp(_))
+        ));
+
+        let mut tampered = entry.clone();
+        tampered.uuid = "24296fb24b8ad77a".to_owned() + &"00".repeat(32);
+        assert!(matches!(
+            tampered.verify(&keyring),
+            Err(LogEntryError::UuidMismatch { .. })
+        ));
+
+        let mut tampered = entry.clone();
+        tampered.verification.inclusion_proof = None;
+        assert!(matches!(
+            tampered.verify(&keyring),
+            Err(LogEntryError::InclusionProofMissing)
+        ));
+
+        let mut tampered = entry.clone();
+        if let Some(proof) = &mut tampered.verification.inclusion_proof {
+            proof.root_hash = "00".repeat(32);
+        }
+        assert!(matches!(
+            tampered.verify(&keyring),
+            Err(LogEntryError::InclusionProof(_))
+        ));
+
+        let (_, other_keyring, _) = log_signer();
+        assert!(matches!(
+            entry.verify(&other_keyring),
+            Err(LogEntryError::SignedEntryTimestamp(_))
+        ));
+    }
+}
@@ -2,8 +2,18 @@ pub mod alpine;
 pub use self::alpine::Alpine;
 pub mod alpine_all_of;
 pub use self::alpine_all_of::AlpineAllOf;
+pub mod checkpoint;
+pub use self::checkpoint::SignedCheckpoint;
 pub mod consistency_proof;
 pub use self::consistency_proof::ConsistencyProof;
+pub mod cose;
+pub use self::cose::Cose;
+pub mod cose_all_of;
+pub use self::cose_all_of::CoseAllOf;
+pub mod dsse;
+pub use self::dsse::Dsse;
+pub mod dsse_all_of;
+pub use self::dsse_all_of::DsseAllOf;
 pub mod error;
 pub use self::error::Error;
 pub mod hashedrekord;
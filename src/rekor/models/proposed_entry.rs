@@ -19,6 +19,20 @@ pub enum ProposedEntry {
         #[serde(rename = "spec")]
         spec: serde_json::Value,
     },
+    #[serde(rename = "cose")]
+    Cose {
+        #[serde(rename = "apiVersion")]
+        api_version: String,
+        #[serde(rename = "spec")]
+        spec: super::cose::Spec,
+    },
+    #[serde(rename = "dsse")]
+    Dsse {
+        #[serde(rename = "apiVersion")]
+        api_version: String,
+        #[serde(rename = "spec")]
+        spec: super::dsse::Spec,
+    },
     #[serde(rename = "hashedrekord")]
     Hashedrekord {
         #[serde(rename = "apiVersion")]
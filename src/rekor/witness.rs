@@ -0,0 +1,461 @@
+//
+// Copyright 2024 The Sigstore Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Consistency checking of Rekor checkpoints over time.
+//!
+//! A [`CheckpointStore`] remembers the last checkpoint seen from each log, and only accepts
+//! checkpoints that are provably consistent with it. A log presenting different views to different
+//! clients, or rewriting its history, is caught as soon as one of its checkpoints fails this check.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use thiserror::Error;
+
+use crate::crypto::keyring::Keyring;
+use crate::crypto::merkle::{self, MerkleHash, MerkleProofError};
+use crate::rekor::models::checkpoint::{CheckpointError, CheckpointNote, SignedCheckpoint};
+use crate::rekor::models::ConsistencyProof;
+
+#[derive(Error, Debug)]
+pub enum WitnessError {
+    #[error(transparent)]
+    Checkpoint(#[from] CheckpointError),
+
+    #[error("consistency proof is malformed")]
+    ConsistencyProofMalformed,
+
+    #[error("a consistency proof between tree sizes {0} and {1} is required")]
+    ConsistencyProofMissing(u64, u64),
+
+    #[error("consistency proof verification failed")]
+    ConsistencyProof(#[source] MerkleProofError),
+
+    #[error("checkpoint store is malformed: {0}")]
+    StoreMalformed(String),
+
+    #[error("checkpoint store I/O failed")]
+    Io(#[from] io::Error),
+}
+
+/// Verifies that the tree committed to by `checkpoint` is an append-only extension of the
+/// previously trusted tree of size `old_size` with root `old_root`.
+///
+/// `proof` is the consistency proof between both trees, as returned by
+/// [`get_log_proof`](crate::rekor::apis::tlog_api::get_log_proof). The checkpoint's signature is
+/// not checked here; see [`SignedCheckpoint::verify_signature`].
+pub fn verify_consistency(
+    old_size: u64,
+    old_root: &[u8],
+    checkpoint: &CheckpointNote,
+    proof: &ConsistencyProof,
+) -> Result<(), WitnessError> {
+    let root_hash =
+        hex::decode(&proof.root_hash).or(Err(WitnessError::ConsistencyProofMalformed))?;
+    let hashes = proof
+        .hashes
+        .iter()
+        .map(hex::decode)
+        .collect::<Result<Vec<_>, _>>()
+        .or(Err(WitnessError::ConsistencyProofMalformed))?;
+
+    if root_hash[..] != checkpoint.hash[..] {
+        return Err(WitnessError::Checkpoint(
+            CheckpointError::RootHashMismatch {
+                checkpoint: hex::encode(checkpoint.hash),
+                proof: proof.root_hash.clone(),
+            },
+        ));
+    }
+
+    merkle::verify_consistency(
+        old_size,
+        checkpoint.size,
+        &hashes,
+        old_root,
+        &checkpoint.hash,
+    )
+    .map_err(WitnessError::ConsistencyProof)
+}
+
+/// A persistent store of the latest checkpoint seen from each log, keyed by log ID.
+///
+/// The store is kept as a JSON file mapping hex-encoded log IDs to signed checkpoints, which
+/// doubles as evidence should a log ever be caught misbehaving.
+#[derive(Debug)]
+pub struct CheckpointStore {
+    path: PathBuf,
+    checkpoints: BTreeMap<String, SignedCheckpoint>,
+}
+
+impl CheckpointStore {
+    /// Opens the store persisted at `path`, or creates an empty one if the file does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, WitnessError> {
+        let path = path.as_ref().to_owned();
+        let contents = match fs::read(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(Self {
+                    path,
+                    checkpoints: Default::default(),
+                })
+            }
+            Err(e) => return Err(e)?,
+        };
+
+        let checkpoints: BTreeMap<String, String> = serde_json::from_slice(&contents)
+            .map_err(|e| WitnessError::StoreMalformed(e.to_string()))?;
+        let checkpoints = checkpoints
+            .into_iter()
+            .map(|(log_id, checkpoint)| Ok((log_id, checkpoint.parse()?)))
+            .collect::<Result<_, CheckpointError>>()
+            .map_err(|e| WitnessError::StoreMalformed(e.to_string()))?;
+
+        Ok(Self { path, checkpoints })
+    }
+
+    /// Returns the latest checkpoint seen from the log identified by `log_id`.
+    pub fn get(&self, log_id: &[u8]) -> Option<&SignedCheckpoint> {
+        self.checkpoints.get(&hex::encode(log_id))
+    }
+
+    /// Verifies `checkpoint` and records it as the latest checkpoint of the log identified by
+    /// `log_id`.
+    ///
+    /// The checkpoint must be signed by the log's key in `keyring`, which must be currently valid.
+    /// If a checkpoint of this log
+    /// was seen before, `proof` must prove the consistency between the smaller and the larger of
+    /// both trees; it may only be omitted when they have the same size, in which case their root
+    /// hashes must match. A checkpoint older than the stored one is checked, but not recorded.
+    pub fn update(
+        &mut self,
+        keyring: &Keyring,
+        log_id: &[u8],
+        checkpoint: SignedCheckpoint,
+        proof: Option<&ConsistencyProof>,
+    ) -> Result<(), WitnessError> {
+        checkpoint.verify_signature_at(keyring, log_id, Utc::now())?;
+
+        let key = hex::encode(log_id);
+        if let Some(stored) = self.checkpoints.get(&key) {
+            let (old, new) = if checkpoint.note.size >= stored.note.size {
+                (&stored.note, &checkpoint.note)
+            } else {
+                (&checkpoint.note, &stored.note)
+            };
+
+            match proof {
+                Some(proof) => verify_consistency(old.size, &old.hash, new, proof)?,
+                None if old.size == new.size => {
+                    let no_proof: &[MerkleHash] = &[];
+                    merkle::verify_consistency(old.size, new.size, no_proof, &old.hash, &new.hash)
+                        .map_err(WitnessError::ConsistencyProof)?
+                }
+                None => return Err(WitnessError::ConsistencyProofMissing(old.size, new.size)),
+            }
+
+            if checkpoint.note.size <= stored.note.size {
+                return Ok(());
+            }
+        }
+
+        self.checkpoints.insert(key, checkpoint);
+        self.persist()
+    }
+
+    /// Writes the store to disk, replacing the previous file atomically.
+    fn persist(&self) -> Result<(), WitnessError> {
+        let checkpoints: BTreeMap<_, _> = self
+            .checkpoints
+            .iter()
+            .map(|(log_id, checkpoint)| (log_id, checkpoint.marshal()))
+            .collect();
+        let contents = serde_json::to_vec_pretty(&checkpoints)
+            .map_err(|e| WitnessError::StoreMalformed(e.to_string()))?;
+
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, contents)?;
+        fs::rename(&tmp_path, &self.path)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose::STANDARD as base64, Engine as _};
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::crypto::keyring::KeyringError;
+    use crate::crypto::merkle::{hash_children, hash_leaf};
+    use crate::crypto::SigStoreSigner;
+    use crate::rekor::models::checkpoint::tests::{log_signer, sign};
+    use crate::trust::{HashAlgorithm, TransparencyLogKey, ValidityPeriod};
+
+    struct Log {
+        signer: SigStoreSigner,
+        keyring: Keyring,
+        log_id: [u8; 32],
+    }
+
+    impl Log {
+        fn new() -> Self {
+            let (signer, keyring, log_id) = log_signer();
+            Self {
+                signer,
+                keyring,
+                log_id,
+            }
+        }
+
+        fn checkpoint(&self, size: u64, root: &MerkleHash) -> SignedCheckpoint {
+            let note = format!("rekor.test\n{size}\n{}\n", base64.encode(root));
+            sign(&self.signer, &self.log_id, &note)
+                .parse()
+                .expect("failed to parse checkpoint")
+        }
+    }
+
+    fn proof(root: &MerkleHash, hashes: &[MerkleHash]) -> ConsistencyProof {
+        ConsistencyProof::new(hex::encode(root), hashes.iter().map(hex::encode).collect())
+    }
+
+    /// Roots of the trees of sizes 1, 2 and 3 over leaves `a`, `b`, `c`, and the leaf hashes.
+    fn trees() -> ([MerkleHash; 3], [MerkleHash; 3]) {
+        let leaves = [hash_leaf(b"a"), hash_leaf(b"b"), hash_leaf(b"c")];
+        let root2 = hash_children(&leaves[0], &leaves[1]);
+        let roots = [leaves[0], root2, hash_children(&root2, &leaves[2])];
+        (roots, leaves)
+    }
+
+    #[test]
+    fn verify_consistency_with_checkpoint() {
+        let log = Log::new();
+        let (roots, leaves) = trees();
+        let checkpoint = log.checkpoint(3, &roots[2]);
+
+        assert!(verify_consistency(
+            2,
+            &roots[1],
+            &checkpoint.note,
+            &proof(&roots[2], &leaves[2..])
+        )
+        .is_ok());
+        assert!(matches!(
+            verify_consistency(
+                2,
+                &roots[1],
+                &checkpoint.note,
+                &proof(&roots[1], &leaves[2..])
+            ),
+            Err(WitnessError::Checkpoint(
+                CheckpointError::RootHashMismatch { .. }
+            ))
+        ));
+        assert!(matches!(
+            verify_consistency(
+                2,
+                &roots[0],
+                &checkpoint.note,
+                &proof(&roots[2], &leaves[2..])
+            ),
+            Err(WitnessError::ConsistencyProof(_))
+        ));
+        assert!(matches!(
+            verify_consistency(
+                2,
+                &roots[1],
+                &checkpoint.note,
+                &ConsistencyProof::new(hex::encode(roots[2]), vec!["zz".into()])
+            ),
+            Err(WitnessError::ConsistencyProofMalformed)
+        ));
+    }
+
+    #[test]
+    fn store_tracks_latest_checkpoint() {
+        let dir = TempDir::new().expect("cannot create temp dir");
+        let path = dir.path().join("checkpoints.json");
+        let log = Log::new();
+        let (roots, leaves) = trees();
+
+        let mut store = CheckpointStore::open(&path).expect("failed to open store");
+        assert!(store.get(&log.log_id).is_none());
+
+        store
+            .update(
+                &log.keyring,
+                &log.log_id,
+                log.checkpoint(1, &roots[0]),
+                None,
+            )
+            .expect("failed to record first checkpoint");
+        store
+            .update(
+                &log.keyring,
+                &log.log_id,
+                log.checkpoint(2, &roots[1]),
+                Some(&proof(&roots[1], &leaves[1..2])),
+            )
+            .expect("failed to record consistent checkpoint");
+
+        // Older and identical checkpoints are checked, but do not replace the latest one.
+        store
+            .update(
+                &log.keyring,
+                &log.log_id,
+                log.checkpoint(1, &roots[0]),
+                Some(&proof(&roots[1], &leaves[1..2])),
+            )
+            .expect("failed to check older checkpoint");
+        store
+            .update(
+                &log.keyring,
+                &log.log_id,
+                log.checkpoint(2, &roots[1]),
+                None,
+            )
+            .expect("failed to check identical checkpoint");
+
+        let store = CheckpointStore::open(&path).expect("failed to reopen store");
+        let latest = store.get(&log.log_id).expect("checkpoint not persisted");
+        assert_eq!(latest.note.size, 2);
+        assert_eq!(latest.note.hash, roots[1]);
+    }
+
+    #[test]
+    fn store_detects_split_view() {
+        let dir = TempDir::new().expect("cannot create temp dir");
+        let log = Log::new();
+        let (roots, leaves) = trees();
+
+        let mut store = CheckpointStore::open(dir.path().join("checkpoints.json"))
+            .expect("failed to open store");
+        store
+            .update(
+                &log.keyring,
+                &log.log_id,
+                log.checkpoint(2, &roots[1]),
+                None,
+            )
+            .expect("failed to record first checkpoint");
+
+        // Same size, different root.
+        assert!(matches!(
+            store.update(
+                &log.keyring,
+                &log.log_id,
+                log.checkpoint(2, &roots[0]),
+                None
+            ),
+            Err(WitnessError::ConsistencyProof(_))
+        ));
+
+        // A larger tree whose history differs from the stored one.
+        let forked = hash_children(&hash_children(&leaves[1], &leaves[0]), &leaves[2]);
+        assert!(matches!(
+            store.update(
+                &log.keyring,
+                &log.log_id,
+                log.checkpoint(3, &forked),
+                Some(&proof(&forked, &leaves[2..])),
+            ),
+            Err(WitnessError::ConsistencyProof(_))
+        ));
+
+        // A larger tree without a proof.
+        assert!(matches!(
+            store.update(
+                &log.keyring,
+                &log.log_id,
+                log.checkpoint(3, &roots[2]),
+                None
+            ),
+            Err(WitnessError::ConsistencyProofMissing(2, 3))
+        ));
+
+        assert_eq!(store.get(&log.log_id).map(|c| c.note.hash), Some(roots[1]));
+    }
+
+    #[test]
+    fn store_rejects_unsigned_checkpoint() {
+        let dir = TempDir::new().expect("cannot create temp dir");
+        let (log, other) = (Log::new(), Log::new());
+        let (roots, _) = trees();
+
+        let mut store = CheckpointStore::open(dir.path().join("checkpoints.json"))
+            .expect("failed to open store");
+        assert!(matches!(
+            store.update(
+                &log.keyring,
+                &log.log_id,
+                other.checkpoint(1, &roots[0]),
+                None
+            ),
+            Err(WitnessError::Checkpoint(
+                CheckpointError::SignatureNotFound(_)
+            ))
+        ));
+        assert!(store.get(&log.log_id).is_none());
+    }
+
+    #[test]
+    fn store_rejects_expired_key() {
+        let dir = TempDir::new().expect("cannot create temp dir");
+        let log = Log::new();
+        let (roots, _) = trees();
+        let public_key = log
+            .signer
+            .to_sigstore_keypair()
+            .and_then(|k| k.public_key_to_der())
+            .expect("failed to export public key");
+        let keyring = Keyring::from_log_keys([TransparencyLogKey {
+            log_id: log.log_id.to_vec(),
+            hash_algorithm: HashAlgorithm::Sha2_256,
+            public_key: &public_key,
+            valid_for: ValidityPeriod {
+                start: None,
+                end: Some(Utc::now() - chrono::Duration::days(1)),
+            },
+        }])
+        .expect("failed to build keyring");
+
+        let mut store = CheckpointStore::open(dir.path().join("checkpoints.json"))
+            .expect("failed to open store");
+        assert!(matches!(
+            store.update(&keyring, &log.log_id, log.checkpoint(1, &roots[0]), None),
+            Err(WitnessError::Checkpoint(
+                CheckpointError::VerificationFailed(KeyringError::KeyNotValid(..))
+            ))
+        ));
+        assert!(store.get(&log.log_id).is_none());
+    }
+
+    #[test]
+    fn store_malformed() {
+        let dir = TempDir::new().expect("cannot create temp dir");
+        let path = dir.path().join("checkpoints.json");
+        fs::write(&path, r#"{"00": "not a checkpoint"}"#).expect("cannot write store");
+
+        assert!(matches!(
+            CheckpointStore::open(&path),
+            Err(WitnessError::StoreMalformed(_))
+        ));
+    }
+}
@@ -13,16 +13,131 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use chrono::{DateTime, Utc};
 use webpki::types::CertificateDer;
 
+use crate::crypto::keyring::key_id;
+
+pub mod signing_config;
 #[cfg(feature = "sigstore-trust-root")]
 pub mod sigstore;
 
+pub use signing_config::{ServiceSelector, SigningConfig};
+
+/// The time range during which a key or certificate authority is trusted. Unset bounds are open.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ValidityPeriod {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl ValidityPeriod {
+    /// Returns `true` if `time` falls within the period, bounds included.
+    pub fn contains(&self, time: DateTime<Utc>) -> bool {
+        let started = match self.start {
+            Some(start) => start <= time,
+            None => true,
+        };
+        let ended = match self.end {
+            Some(end) => end < time,
+            None => false,
+        };
+
+        started && !ended
+    }
+}
+
+/// The hash algorithm a transparency log builds its Merkle tree with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha2_256,
+    Sha2_384,
+    Sha2_512,
+    Sha3_256,
+    Sha3_384,
+}
+
+/// The public key of a transparency log (Rekor or CT) instance.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransparencyLogKey<'a> {
+    /// The log ID, usually the SHA-256 digest of `public_key`.
+    pub log_id: Vec<u8>,
+    pub hash_algorithm: HashAlgorithm,
+    /// The DER-encoded `SubjectPublicKeyInfo` of the log's key.
+    pub public_key: &'a [u8],
+    pub valid_for: ValidityPeriod,
+}
+
+impl<'a> TransparencyLogKey<'a> {
+    /// Describes a SHA-256 log from its DER-encoded key alone, trusted at any time.
+    fn from_key(public_key: &'a [u8]) -> Self {
+        Self {
+            log_id: key_id(public_key).to_vec(),
+            hash_algorithm: HashAlgorithm::Sha2_256,
+            public_key,
+            valid_for: ValidityPeriod::default(),
+        }
+    }
+}
+
+/// A certificate authority (Fulcio or a timestamp authority) and its certificate chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CertificateAuthority<'a> {
+    /// The authority's chain, leaf first.
+    pub cert_chain: Vec<CertificateDer<'a>>,
+    pub valid_for: ValidityPeriod,
+}
+
 /// A `TrustRoot` owns all key material necessary for establishing a root of trust.
 pub trait TrustRoot {
     fn fulcio_certs(&self) -> crate::errors::Result<Vec<CertificateDer>>;
     fn rekor_keys(&self) -> crate::errors::Result<Vec<&[u8]>>;
     fn ctfe_keys(&self) -> crate::errors::Result<Vec<&[u8]>>;
+
+    /// Certificate chains of the timestamp authorities trusted to issue signed timestamps.
+    fn tsa_certs(&self) -> crate::errors::Result<Vec<CertificateDer<'_>>> {
+        Ok(Vec::new())
+    }
+
+    /// Keys of the Rekor instances, including the ones no longer active, with the time ranges
+    /// they were used in.
+    ///
+    /// Defaults to [`TrustRoot::rekor_keys`], trusted at any time.
+    fn rekor_log_keys(&self) -> crate::errors::Result<Vec<TransparencyLogKey<'_>>> {
+        Ok(self
+            .rekor_keys()?
+            .into_iter()
+            .map(TransparencyLogKey::from_key)
+            .collect())
+    }
+
+    /// Keys of the CT logs, including the ones no longer active, with the time ranges they were
+    /// used in.
+    ///
+    /// Defaults to [`TrustRoot::ctfe_keys`], trusted at any time.
+    fn ctfe_log_keys(&self) -> crate::errors::Result<Vec<TransparencyLogKey<'_>>> {
+        Ok(self
+            .ctfe_keys()?
+            .into_iter()
+            .map(TransparencyLogKey::from_key)
+            .collect())
+    }
+
+    /// The timestamp authorities trusted to issue signed timestamps, with the time ranges they
+    /// were used in.
+    ///
+    /// Defaults to a single authority holding [`TrustRoot::tsa_certs`], trusted at any time.
+    fn timestamp_authorities(&self) -> crate::errors::Result<Vec<CertificateAuthority<'_>>> {
+        let cert_chain = self.tsa_certs()?;
+        if cert_chain.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        Ok(vec![CertificateAuthority {
+            cert_chain,
+            valid_for: ValidityPeriod::default(),
+        }])
+    }
 }
 
 /// A `ManualTrustRoot` is a [TrustRoot] with out-of-band trust materials.
@@ -32,6 +147,7 @@ pub struct ManualTrustRoot<'a> {
     pub fulcio_certs: Option<Vec<CertificateDer<'a>>>,
     pub rekor_key: Option<Vec<u8>>,
     pub ctfe_keys: Vec<Vec<u8>>,
+    pub tsa_certs: Vec<CertificateDer<'a>>,
 }
 
 impl TrustRoot for ManualTrustRoot<'_> {
@@ -52,4 +168,46 @@ impl TrustRoot for ManualTrustRoot<'_> {
     fn ctfe_keys(&self) -> crate::errors::Result<Vec<&[u8]>> {
         Ok(self.ctfe_keys.iter().map(|v| &v[..]).collect())
     }
+
+    fn tsa_certs(&self) -> crate::errors::Result<Vec<CertificateDer<'_>>> {
+        Ok(self.tsa_certs.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn validity_period_contains() {
+        let at = |secs| Utc.timestamp_opt(secs, 0).unwrap();
+        let period = ValidityPeriod {
+            start: Some(at(100)),
+            end: Some(at(200)),
+        };
+
+        assert!(!period.contains(at(99)));
+        assert!(period.contains(at(100)));
+        assert!(period.contains(at(200)));
+        assert!(!period.contains(at(201)));
+        assert!(ValidityPeriod::default().contains(at(0)));
+    }
+
+    #[test]
+    fn manual_log_keys() {
+        let root = ManualTrustRoot {
+            rekor_key: Some(b"rekor key".to_vec()),
+            ..Default::default()
+        };
+
+        let keys = root.rekor_log_keys().unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].log_id, key_id(b"rekor key"));
+        assert_eq!(keys[0].public_key, b"rekor key");
+        assert_eq!(keys[0].valid_for, ValidityPeriod::default());
+        assert!(root.ctfe_log_keys().unwrap().is_empty());
+        assert!(root.timestamp_authorities().unwrap().is_empty());
+    }
 }
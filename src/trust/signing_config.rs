@@ -0,0 +1,411 @@
+//
+// Copyright 2024 The Sigstore Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Signing configurations, describing the services a signer connects to.
+//!
+//! Signing configurations are distributed alongside the trusted root, as `signing_config.json`.
+//! Both the v0.1 and v0.2 formats are supported.
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use url::Url;
+
+use super::ValidityPeriod;
+use crate::errors::{Result, SigstoreError};
+
+/// The media type of v0.1 signing configurations.
+pub const MEDIA_TYPE_V0_1: &str = "application/vnd.dev.sigstore.signingconfig.v0.1+json";
+
+/// The media type of v0.2 signing configurations.
+pub const MEDIA_TYPE_V0_2: &str = "application/vnd.dev.sigstore.signingconfig.v0.2+json";
+
+/// The major API versions of the services supported by this crate.
+const CA_API_VERSION: u32 = 1;
+const OIDC_API_VERSION: u32 = 1;
+const TLOG_API_VERSION: u32 = 1;
+const TSA_API_VERSION: u32 = 1;
+
+/// A service endpoint of a [`SigningConfig`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Service {
+    pub url: Url,
+    pub major_api_version: u32,
+    pub valid_for: ValidityPeriod,
+    /// The operator of the service, e.g. `sigstore.dev`. Empty for v0.1 configurations.
+    pub operator: String,
+}
+
+/// How many of the transparency logs or timestamp authorities of a [`SigningConfig`] a signer
+/// must use.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ServiceSelector {
+    /// Any one of the valid services.
+    #[default]
+    Any,
+    /// Every valid service.
+    All,
+    /// Exactly this number of the valid services.
+    Exact(u32),
+}
+
+/// The services a signer connects to: certificate authorities (Fulcio), OIDC providers,
+/// transparency logs (Rekor) and timestamp authorities.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SigningConfig {
+    pub ca_urls: Vec<Service>,
+    pub oidc_urls: Vec<Service>,
+    pub tlog_urls: Vec<Service>,
+    pub tsa_urls: Vec<Service>,
+    /// The transparency logs to upload entries to. Always [`ServiceSelector::Any`] for v0.1
+    /// configurations.
+    pub tlog_selector: ServiceSelector,
+    /// The timestamp authorities to request signed timestamps from. Always
+    /// [`ServiceSelector::Any`] for v0.1 configurations.
+    pub tsa_selector: ServiceSelector,
+}
+
+impl SigningConfig {
+    /// Parses the contents of a `signing_config.json` file.
+    pub fn from_json(data: &[u8]) -> Result<Self> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct MediaType {
+            media_type: String,
+        }
+
+        match serde_json::from_slice::<MediaType>(data)?
+            .media_type
+            .as_str()
+        {
+            MEDIA_TYPE_V0_1 => Ok(serde_json::from_slice::<SigningConfigV01>(data)?.into()),
+            MEDIA_TYPE_V0_2 => serde_json::from_slice::<SigningConfigV02>(data)?.try_into(),
+            media_type => Err(SigstoreError::SigningConfigError(format!(
+                "unsupported media type {media_type}"
+            ))),
+        }
+    }
+
+    /// Reads a `signing_config.json` file from disk.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_json(&std::fs::read(path)?)
+    }
+
+    /// Returns the certificate authority to request signing certificates from.
+    pub fn ca(&self) -> Option<&Service> {
+        select(&self.ca_urls, CA_API_VERSION)
+    }
+
+    /// Returns the OIDC provider to request identity tokens from.
+    pub fn oidc(&self) -> Option<&Service> {
+        select(&self.oidc_urls, OIDC_API_VERSION)
+    }
+
+    /// Returns the transparency log to upload entries to.
+    pub fn tlog(&self) -> Option<&Service> {
+        self.tlogs().next()
+    }
+
+    /// Returns the timestamp authority to request signed timestamps from, if any.
+    pub fn tsa(&self) -> Option<&Service> {
+        self.tsas().next()
+    }
+
+    /// Returns the currently valid transparency logs, to be picked from by
+    /// [`SigningConfig::tlog_selector`].
+    pub fn tlogs(&self) -> impl Iterator<Item = &Service> {
+        valid(&self.tlog_urls, TLOG_API_VERSION)
+    }
+
+    /// Returns the currently valid timestamp authorities, to be picked from by
+    /// [`SigningConfig::tsa_selector`].
+    pub fn tsas(&self) -> impl Iterator<Item = &Service> {
+        valid(&self.tsa_urls, TSA_API_VERSION)
+    }
+}
+
+/// Selects the first service with the given API version that is currently valid.
+fn select(services: &[Service], major_api_version: u32) -> Option<&Service> {
+    valid(services, major_api_version).next()
+}
+
+/// Returns the services with the given API version that are currently valid.
+fn valid(services: &[Service], major_api_version: u32) -> impl Iterator<Item = &Service> {
+    let now = Utc::now();
+
+    services.iter().filter(move |service| {
+        service.major_api_version == major_api_version && service.valid_for.contains(now)
+    })
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SigningConfigV01 {
+    ca_url: Url,
+    oidc_url: Url,
+    #[serde(default)]
+    tlog_urls: Vec<Url>,
+    #[serde(default)]
+    tsa_urls: Vec<Url>,
+}
+
+impl From<SigningConfigV01> for SigningConfig {
+    fn from(config: SigningConfigV01) -> Self {
+        // v0.1 services have no metadata: they are assumed to serve the first API version.
+        let services = |urls: Vec<Url>| {
+            urls.into_iter()
+                .map(|url| Service {
+                    url,
+                    major_api_version: 1,
+                    valid_for: ValidityPeriod::default(),
+                    operator: String::new(),
+                })
+                .collect()
+        };
+
+        Self {
+            ca_urls: services(vec![config.ca_url]),
+            oidc_urls: services(vec![config.oidc_url]),
+            tlog_urls: services(config.tlog_urls),
+            tsa_urls: services(config.tsa_urls),
+            tlog_selector: ServiceSelector::Any,
+            tsa_selector: ServiceSelector::Any,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SigningConfigV02 {
+    #[serde(default)]
+    ca_urls: Vec<ServiceV02>,
+    #[serde(default)]
+    oidc_urls: Vec<ServiceV02>,
+    #[serde(default)]
+    rekor_tlog_urls: Vec<ServiceV02>,
+    #[serde(default)]
+    tsa_urls: Vec<ServiceV02>,
+    #[serde(default)]
+    rekor_tlog_config: ServiceConfigurationV02,
+    #[serde(default)]
+    tsa_config: ServiceConfigurationV02,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServiceV02 {
+    url: Url,
+    major_api_version: u32,
+    #[serde(default)]
+    valid_for: TimeRange,
+    #[serde(default)]
+    operator: String,
+}
+
+#[derive(Default, Deserialize)]
+struct ServiceConfigurationV02 {
+    #[serde(default)]
+    selector: Option<String>,
+    #[serde(default)]
+    count: Option<u32>,
+}
+
+impl TryFrom<ServiceConfigurationV02> for ServiceSelector {
+    type Error = SigstoreError;
+
+    fn try_from(config: ServiceConfigurationV02) -> Result<Self> {
+        match (config.selector.as_deref(), config.count) {
+            (None | Some("ANY"), _) => Ok(Self::Any),
+            (Some("ALL"), _) => Ok(Self::All),
+            (Some("EXACT"), Some(count)) => Ok(Self::Exact(count)),
+            (Some("EXACT"), None) => Err(SigstoreError::SigningConfigError(
+                "EXACT service selector without a count".into(),
+            )),
+            (Some(selector), _) => Err(SigstoreError::SigningConfigError(format!(
+                "unsupported service selector {selector}"
+            ))),
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+struct TimeRange {
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<SigningConfigV02> for SigningConfig {
+    type Error = SigstoreError;
+
+    fn try_from(config: SigningConfigV02) -> Result<Self> {
+        let services = |services: Vec<ServiceV02>| {
+            services
+                .into_iter()
+                .map(|service| Service {
+                    url: service.url,
+                    major_api_version: service.major_api_version,
+                    valid_for: ValidityPeriod {
+                        start: service.valid_for.start,
+                        end: service.valid_for.end,
+                    },
+                    operator: service.operator,
+                })
+                .collect()
+        };
+
+        Ok(Self {
+            ca_urls: services(config.ca_urls),
+            oidc_urls: services(config.oidc_urls),
+            tlog_urls: services(config.rekor_tlog_urls),
+            tsa_urls: services(config.tsa_urls),
+            tlog_selector: config.rekor_tlog_config.try_into()?,
+            tsa_selector: config.tsa_config.try_into()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signing_config_v0_1() {
+        let config = SigningConfig::from_json(
+            br#"{
+                "mediaType": "application/vnd.dev.sigstore.signingconfig.v0.1+json",
+                "caUrl": "https://fulcio.sigstore.dev",
+                "oidcUrl": "https://oauth2.sigstore.dev/auth",
+                "tlogUrls": ["https://rekor.sigstore.dev"],
+                "tsaUrls": ["https://timestamp.sigstore.dev/api/v1/timestamp"]
+            }"#,
+        )
+        .expect("failed to parse signing config");
+
+        assert_eq!(
+            config.ca().map(|s| s.url.as_str()),
+            Some("https://fulcio.sigstore.dev/")
+        );
+        assert_eq!(
+            config.oidc().map(|s| s.url.as_str()),
+            Some("https://oauth2.sigstore.dev/auth")
+        );
+        assert_eq!(
+            config.tlog().map(|s| s.url.as_str()),
+            Some("https://rekor.sigstore.dev/")
+        );
+        assert_eq!(
+            config.tsa().map(|s| s.url.as_str()),
+            Some("https://timestamp.sigstore.dev/api/v1/timestamp")
+        );
+    }
+
+    #[test]
+    fn signing_config_v0_2() {
+        let config = SigningConfig::from_json(
+            br#"{
+                "mediaType": "application/vnd.dev.sigstore.signingconfig.v0.2+json",
+                "caUrls": [{
+                    "url": "https://fulcio.example.com",
+                    "majorApiVersion": 1,
+                    "validFor": {"start": "2023-04-14T21:38:40Z"},
+                    "operator": "example.com"
+                }],
+                "oidcUrls": [{
+                    "url": "https://oauth2.example.com/auth",
+                    "majorApiVersion": 1,
+                    "validFor": {"start": "2025-04-16T00:00:00Z"}
+                }],
+                "rekorTlogUrls": [
+                    {
+                        "url": "https://rekor-v2.example.com",
+                        "majorApiVersion": 2,
+                        "validFor": {"start": "2025-09-01T00:00:00Z"}
+                    },
+                    {
+                        "url": "https://rekor-old.example.com",
+                        "majorApiVersion": 1,
+                        "validFor": {"start": "2021-01-12T11:53:27Z", "end": "2022-01-12T11:53:27Z"}
+                    },
+                    {
+                        "url": "https://rekor.example.com",
+                        "majorApiVersion": 1,
+                        "validFor": {"start": "2022-01-12T11:53:27Z"}
+                    }
+                ],
+                "rekorTlogConfig": {"selector": "ANY"},
+                "tsaConfig": {"selector": "ANY"}
+            }"#,
+        )
+        .expect("failed to parse signing config");
+
+        let ca = config.ca().expect("missing CA");
+        assert_eq!(ca.url.as_str(), "https://fulcio.example.com/");
+        assert_eq!(ca.operator, "example.com");
+        assert_eq!(
+            config.tlog().map(|s| s.url.as_str()),
+            Some("https://rekor.example.com/")
+        );
+        assert!(config.tsa().is_none());
+        assert_eq!(config.tlog_selector, ServiceSelector::Any);
+        assert_eq!(config.tsa_selector, ServiceSelector::Any);
+    }
+
+    #[test]
+    fn signing_config_v0_2_selectors() {
+        let config = |tlog_config: &str, tsa_config: &str| {
+            SigningConfig::from_json(
+                format!(
+                    r#"{{
+                        "mediaType": "application/vnd.dev.sigstore.signingconfig.v0.2+json",
+                        "rekorTlogConfig": {tlog_config},
+                        "tsaConfig": {tsa_config}
+                    }}"#
+                )
+                .as_bytes(),
+            )
+        };
+
+        let parsed = config(
+            r#"{"selector": "ALL"}"#,
+            r#"{"selector": "EXACT", "count": 2}"#,
+        )
+        .expect("failed to parse signing config");
+        assert_eq!(parsed.tlog_selector, ServiceSelector::All);
+        assert_eq!(parsed.tsa_selector, ServiceSelector::Exact(2));
+
+        let parsed = config("{}", "{}").expect("failed to parse signing config");
+        assert_eq!(parsed.tlog_selector, ServiceSelector::Any);
+        assert_eq!(parsed.tsa_selector, ServiceSelector::Any);
+
+        for (tlog_config, tsa_config) in [
+            (r#"{"selector": "EXACT"}"#, "{}"),
+            ("{}", r#"{"selector": "SERVICE_SELECTOR_UNDEFINED"}"#),
+        ] {
+            assert!(matches!(
+                config(tlog_config, tsa_config),
+                Err(SigstoreError::SigningConfigError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn signing_config_unsupported() {
+        assert!(matches!(
+            SigningConfig::from_json(br#"{"mediaType": "application/json"}"#),
+            Err(SigstoreError::SigningConfigError(_))
+        ));
+    }
+}
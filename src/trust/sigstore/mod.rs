@@ -22,11 +22,13 @@
 //! to enable Fulcio and Rekor integrations.
 use futures_util::TryStreamExt;
 use sha2::{Digest, Sha256};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tokio_util::bytes::BytesMut;
+use url::Url;
 
+use chrono::DateTime;
 use sigstore_protobuf_specs::dev::sigstore::{
-    common::v1::TimeRange,
+    common::v1::{HashAlgorithm as ProtoHashAlgorithm, TimeRange},
     trustroot::v1::{CertificateAuthority, TransparencyLogInstance, TrustedRoot},
 };
 use tough::TargetName;
@@ -36,12 +38,17 @@ use webpki::types::CertificateDer;
 mod constants;
 
 use crate::errors::{Result, SigstoreError};
+use crate::trust::{HashAlgorithm, SigningConfig, TransparencyLogKey, ValidityPeriod};
 pub use crate::trust::{ManualTrustRoot, TrustRoot};
 
+/// The name of the signing configuration target of the Sigstore Public Good Instance.
+pub const SIGNING_CONFIG_TARGET: &str = "signing_config.v0.2.json";
+
 /// Securely fetches Rekor public key and Fulcio certificates from Sigstore's TUF repository.
 #[derive(Debug)]
 pub struct SigstoreTrustRoot {
     trusted_root: TrustedRoot,
+    signing_config: Option<SigningConfig>,
 }
 
 impl SigstoreTrustRoot {
@@ -49,38 +56,84 @@ impl SigstoreTrustRoot {
     async fn from_tough(
         repository: &tough::Repository,
         checkout_dir: Option<&Path>,
+        target: &str,
+        signing_config_target: Option<&str>,
+        embedded: bool,
     ) -> Result<Self> {
         let trusted_root = {
-            let data = Self::fetch_target(repository, checkout_dir, "trusted_root.json").await?;
+            let data = Self::fetch_target(repository, checkout_dir, target, embedded).await?;
             serde_json::from_slice(&data[..])?
         };
+        let signing_config = match signing_config_target {
+            Some(target) => {
+                let data = Self::fetch_target(repository, checkout_dir, target, embedded).await?;
+                Some(SigningConfig::from_json(&data)?)
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            trusted_root,
+            signing_config,
+        })
+    }
+
+    /// Returns the signing configuration distributed alongside the trusted root, if one was
+    /// requested with [`SigstoreTrustRootBuilder::with_signing_config_target`].
+    pub fn signing_config(&self) -> Option<&SigningConfig> {
+        self.signing_config.as_ref()
+    }
 
-        Ok(Self { trusted_root })
+    /// Constructs a trust root from the contents of a `trusted_root.json` file, the JSON
+    /// serialization of a [`TrustedRoot`].
+    ///
+    /// Unlike [`SigstoreTrustRoot::new`], this does not go through TUF: the caller is
+    /// responsible for obtaining `data` from a trusted source.
+    pub fn from_trusted_root_json(data: &[u8]) -> Result<Self> {
+        let trusted_root = serde_json::from_slice(data)?;
+
+        Ok(Self {
+            trusted_root,
+            signing_config: None,
+        })
+    }
+
+    /// Constructs a trust root from the `trusted_root.json` embedded in this release, without
+    /// going through TUF.
+    pub(crate) fn embedded() -> Result<Self> {
+        Self::from_trusted_root_json(
+            constants::static_resource("trusted_root.json").expect("missing embedded trusted root"),
+        )
+    }
+
+    /// Constructs a trust root from a `trusted_root.json` file on disk.
+    ///
+    /// See [`SigstoreTrustRoot::from_trusted_root_json`].
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_trusted_root_json(&std::fs::read(path)?)
     }
 
     /// Constructs a new trust root backed by the Sigstore Public Good Instance.
     pub async fn new(cache_dir: Option<&Path>) -> Result<Self> {
-        // These are statically defined and should always parse correctly.
-        let metadata_base = url::Url::parse(constants::SIGSTORE_METADATA_BASE)?;
-        let target_base = url::Url::parse(constants::SIGSTORE_TARGET_BASE)?;
-
-        let repository = tough::RepositoryLoader::new(
-            &constants::static_resource("root.json").expect("Failed to fetch embedded TUF root!"),
-            metadata_base,
-            target_base,
-        )
-        .expiration_enforcement(tough::ExpirationEnforcement::Safe)
-        .load()
-        .await
-        .map_err(Box::new)?;
+        let mut builder = Self::builder();
+        if let Some(cache_dir) = cache_dir {
+            builder = builder.with_cache_dir(cache_dir);
+        }
 
-        Self::from_tough(&repository, cache_dir).await
+        builder.build().await
+    }
+
+    /// Returns a [`SigstoreTrustRootBuilder`], to construct a trust root backed by another TUF
+    /// repository than the Sigstore Public Good Instance's.
+    pub fn builder() -> SigstoreTrustRootBuilder {
+        SigstoreTrustRootBuilder::default()
     }
 
     async fn fetch_target<N>(
         repository: &tough::Repository,
         checkout_dir: Option<&Path>,
         name: N,
+        embedded: bool,
     ) -> Result<Vec<u8>>
     where
         N: TryInto<TargetName, Error = tough::error::Error>,
@@ -100,7 +153,9 @@ impl SigstoreTrustRoot {
             debug!("{}: reading from disk cache", name.raw());
             local_data.to_vec()
         // Try reading the target embedded into the binary.
-        } else if let Some(embedded_data) = constants::static_resource(name.raw()) {
+        } else if let Some(embedded_data) =
+            constants::static_resource(name.raw()).filter(|_| embedded)
+        {
             debug!("{}: reading from embedded resources", name.raw());
             embedded_data.to_vec()
         // If all else fails, read the data from the TUF repo.
@@ -155,6 +210,154 @@ impl SigstoreTrustRoot {
             .flat_map(|chain| chain.certificates.iter())
             .map(|cert| cert.raw_bytes.as_slice())
     }
+
+    fn tlog_log_keys(tlogs: &[TransparencyLogInstance]) -> Result<Vec<TransparencyLogKey<'_>>> {
+        tlogs
+            .iter()
+            .map(|tlog| {
+                let public_key = tlog.public_key.as_ref();
+                let hash_algorithm = match ProtoHashAlgorithm::try_from(tlog.hash_algorithm) {
+                    Ok(ProtoHashAlgorithm::Sha2256) => HashAlgorithm::Sha2_256,
+                    Ok(ProtoHashAlgorithm::Sha2384) => HashAlgorithm::Sha2_384,
+                    Ok(ProtoHashAlgorithm::Sha2512) => HashAlgorithm::Sha2_512,
+                    Ok(ProtoHashAlgorithm::Sha3256) => HashAlgorithm::Sha3_256,
+                    Ok(ProtoHashAlgorithm::Sha3384) => HashAlgorithm::Sha3_384,
+                    _ => {
+                        return Err(SigstoreError::TufMetadataError(format!(
+                            "unsupported hash algorithm for log {}",
+                            tlog.base_url
+                        )))
+                    }
+                };
+
+                Ok(TransparencyLogKey {
+                    log_id: tlog
+                        .log_id
+                        .as_ref()
+                        .map(|log_id| log_id.key_id.clone())
+                        .ok_or_else(|| {
+                            SigstoreError::TufMetadataError(format!(
+                                "missing log ID for log {}",
+                                tlog.base_url
+                            ))
+                        })?,
+                    hash_algorithm,
+                    public_key: public_key
+                        .and_then(|key| key.raw_bytes.as_deref())
+                        .ok_or_else(|| {
+                            SigstoreError::TufMetadataError(format!(
+                                "missing public key for log {}",
+                                tlog.base_url
+                            ))
+                        })?,
+                    valid_for: validity_period(
+                        public_key.and_then(|key| key.valid_for.as_ref()),
+                        &format!("log {}", tlog.base_url),
+                    )?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A builder for a [`SigstoreTrustRoot`] backed by a custom TUF repository, for private Sigstore
+/// deployments or staging.
+///
+/// By default, the builder targets the Sigstore Public Good Instance. Repositories can be served
+/// over HTTP(S), or from a local directory with `file://` URLs.
+#[derive(Debug)]
+pub struct SigstoreTrustRootBuilder {
+    metadata_base: Url,
+    targets_base: Url,
+    root: Option<Vec<u8>>,
+    trusted_root_target: String,
+    signing_config_target: Option<String>,
+    cache_dir: Option<PathBuf>,
+}
+
+impl Default for SigstoreTrustRootBuilder {
+    fn default() -> Self {
+        Self {
+            // These are statically defined and should always parse correctly.
+            metadata_base: Url::parse(constants::SIGSTORE_METADATA_BASE)
+                .expect("constant TUF metadata base fails to parse!"),
+            targets_base: Url::parse(constants::SIGSTORE_TARGET_BASE)
+                .expect("constant TUF target base fails to parse!"),
+            root: None,
+            trusted_root_target: "trusted_root.json".to_owned(),
+            signing_config_target: None,
+            cache_dir: None,
+        }
+    }
+}
+
+impl SigstoreTrustRootBuilder {
+    /// Sets the base URL of the repository's TUF metadata.
+    pub fn with_metadata_base(mut self, metadata_base: Url) -> Self {
+        self.metadata_base = metadata_base;
+        self
+    }
+
+    /// Sets the base URL of the repository's TUF targets.
+    pub fn with_targets_base(mut self, targets_base: Url) -> Self {
+        self.targets_base = targets_base;
+        self
+    }
+
+    /// Sets the initial, trusted `root.json` of the repository.
+    ///
+    /// Unless a root is set, the Sigstore Public Good Instance's root is used, and targets
+    /// embedded in this crate may be used in place of the repository's.
+    pub fn with_root(mut self, root: impl Into<Vec<u8>>) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// Sets the name of the `trusted_root.json` target in the repository.
+    pub fn with_trusted_root_target(mut self, name: impl Into<String>) -> Self {
+        self.trusted_root_target = name.into();
+        self
+    }
+
+    /// Also fetches the signing configuration from the target `name`, e.g.
+    /// [`SIGNING_CONFIG_TARGET`] on the Sigstore Public Good Instance. See
+    /// [`SigstoreTrustRoot::signing_config`].
+    pub fn with_signing_config_target(mut self, name: impl Into<String>) -> Self {
+        self.signing_config_target = Some(name.into());
+        self
+    }
+
+    /// Caches targets in `cache_dir`, to be reused while they are up to date.
+    pub fn with_cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(cache_dir.into());
+        self
+    }
+
+    /// Loads the TUF repository and constructs the trust root from its `trusted_root.json`.
+    pub async fn build(self) -> Result<SigstoreTrustRoot> {
+        let embedded = self.root.is_none();
+        let root = match &self.root {
+            Some(root) => root.as_slice(),
+            None => {
+                constants::static_resource("root.json").expect("Failed to fetch embedded TUF root!")
+            }
+        };
+
+        let repository = tough::RepositoryLoader::new(&root, self.metadata_base, self.targets_base)
+            .expiration_enforcement(tough::ExpirationEnforcement::Safe)
+            .load()
+            .await
+            .map_err(Box::new)?;
+
+        SigstoreTrustRoot::from_tough(
+            &repository,
+            self.cache_dir.as_deref(),
+            &self.trusted_root_target,
+            self.signing_config_target.as_deref(),
+            embedded,
+        )
+        .await
+    }
 }
 
 impl crate::trust::TrustRoot for SigstoreTrustRoot {
@@ -210,6 +413,71 @@ impl crate::trust::TrustRoot for SigstoreTrustRoot {
             Ok(keys)
         }
     }
+
+    /// Fetch timestamp authority certificates from the given TUF repository or reuse
+    /// the local cache if it's not outdated.
+    ///
+    /// The contents of the local cache are updated when they are outdated.
+    fn tsa_certs(&self) -> Result<Vec<CertificateDer<'_>>> {
+        // Allow expired certificates: they may have been active when the
+        // timestamp was issued.
+        let certs = Self::ca_keys(&self.trusted_root.timestamp_authorities, true);
+
+        Ok(certs
+            .map(|c| CertificateDer::from(c).into_owned())
+            .collect())
+    }
+
+    fn rekor_log_keys(&self) -> Result<Vec<TransparencyLogKey<'_>>> {
+        Self::tlog_log_keys(&self.trusted_root.tlogs)
+    }
+
+    fn ctfe_log_keys(&self) -> Result<Vec<TransparencyLogKey<'_>>> {
+        Self::tlog_log_keys(&self.trusted_root.ctlogs)
+    }
+
+    fn timestamp_authorities(&self) -> Result<Vec<crate::trust::CertificateAuthority<'_>>> {
+        self.trusted_root
+            .timestamp_authorities
+            .iter()
+            .map(|ca| {
+                Ok(crate::trust::CertificateAuthority {
+                    cert_chain: ca
+                        .cert_chain
+                        .iter()
+                        .flat_map(|chain| chain.certificates.iter())
+                        .map(|cert| CertificateDer::from(cert.raw_bytes.as_slice()))
+                        .collect(),
+                    valid_for: validity_period(
+                        ca.valid_for.as_ref(),
+                        &format!("timestamp authority {}", ca.uri),
+                    )?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Converts the `range` of the trust material described by `subject` to a [`ValidityPeriod`].
+///
+/// Bounds that cannot be represented are rejected, rather than treated as open.
+fn validity_period(range: Option<&TimeRange>, subject: &str) -> Result<ValidityPeriod> {
+    let bound = |seconds: Option<i64>| {
+        seconds
+            .map(|seconds| {
+                DateTime::from_timestamp(seconds, 0).ok_or_else(|| {
+                    SigstoreError::TufMetadataError(format!(
+                        "invalid validity period for {subject}"
+                    ))
+                })
+            })
+            .transpose()
+    };
+
+    Ok(ValidityPeriod {
+        start: bound(range.and_then(|r| r.start.as_ref()).map(|t| t.seconds))?,
+        end: bound(range.and_then(|r| r.end.as_ref()).map(|t| t.seconds))?,
+    })
 }
 
 /// Given a `range`, checks that the the current time is not before `start`. If
@@ -240,6 +508,7 @@ mod tests {
     use super::*;
     use rstest::{fixture, rstest};
     use std::fs;
+    use std::num::NonZeroU64;
     use std::path::Path;
     use std::time::SystemTime;
     use tempfile::TempDir;
@@ -302,6 +571,187 @@ mod tests {
         assert_ne!(data, outdated_data, "TUF cache was not properly updated");
     }
 
+    /// Creates a TUF repository in `dir` serving the embedded `trusted_root.json` and a signing
+    /// configuration, returning its signed `root.json`.
+    async fn tuf_repository(dir: &Path) -> Vec<u8> {
+        use ring::rand::SystemRandom;
+        use tough::editor::{signed::SignedRole, RepositoryEditor};
+        use tough::key_source::{KeySource, LocalKeySource};
+        use tough::schema::{KeyHolder, RoleKeys, RoleType, Root};
+
+        let rng = SystemRandom::new();
+        let key_path = dir.join("key.pk8");
+        let pkcs8 =
+            ring::signature::Ed25519KeyPair::generate_pkcs8(&rng).expect("failed to generate key");
+        fs::write(&key_path, pkcs8.as_ref()).expect("failed to write key");
+        let keys: Vec<Box<dyn KeySource>> = vec![Box::new(LocalKeySource { path: key_path })];
+
+        let key = keys[0]
+            .as_sign()
+            .await
+            .expect("failed to load key")
+            .tuf_key();
+        let key_id = key.key_id().expect("failed to compute key ID");
+        let expires = chrono::Utc::now() + chrono::TimeDelta::days(1);
+        let one = NonZeroU64::new(1).expect("1 is non-zero");
+        let root = Root {
+            spec_version: "1.0.0".to_owned(),
+            consistent_snapshot: false,
+            version: one,
+            expires,
+            keys: [(key_id.clone(), key)].into(),
+            roles: [
+                RoleType::Root,
+                RoleType::Snapshot,
+                RoleType::Targets,
+                RoleType::Timestamp,
+            ]
+            .into_iter()
+            .map(|role| {
+                let keys = RoleKeys {
+                    keyids: vec![key_id.clone()],
+                    threshold: one,
+                    _extra: Default::default(),
+                };
+                (role, keys)
+            })
+            .collect(),
+            _extra: Default::default(),
+        };
+        let root = SignedRole::new(root.clone(), &KeyHolder::Root(root), &keys, &rng)
+            .await
+            .expect("failed to sign root");
+        let root_path = dir.join("root.json");
+        fs::write(&root_path, root.buffer()).expect("failed to write root");
+
+        let targets_dir = dir.join("targets");
+        let metadata_dir = dir.join("metadata");
+        fs::create_dir_all(&targets_dir).expect("failed to create targets dir");
+        fs::create_dir_all(&metadata_dir).expect("failed to create metadata dir");
+        let target_path = targets_dir.join("trusted_root.json");
+        let trusted_root =
+            constants::static_resource("trusted_root.json").expect("missing embedded trusted root");
+        fs::write(&target_path, trusted_root).expect("failed to write target");
+
+        let mut editor = RepositoryEditor::new(&root_path)
+            .await
+            .expect("failed to create editor");
+        editor
+            .snapshot_version(one)
+            .snapshot_expires(expires)
+            .timestamp_version(one)
+            .timestamp_expires(expires)
+            .targets_version(one)
+            .expect("failed to set targets version")
+            .targets_expires(expires)
+            .expect("failed to set targets expiry");
+        let signing_config_path = targets_dir.join("signing_config.json");
+        fs::write(
+            &signing_config_path,
+            br#"{
+                "mediaType": "application/vnd.dev.sigstore.signingconfig.v0.1+json",
+                "caUrl": "https://fulcio.example.com",
+                "oidcUrl": "https://oauth2.example.com/auth",
+                "tlogUrls": ["https://rekor.example.com"]
+            }"#,
+        )
+        .expect("failed to write target");
+
+        editor
+            .add_target_paths(vec![&target_path, &signing_config_path])
+            .await
+            .expect("failed to add targets");
+        editor
+            .sign(&keys)
+            .await
+            .expect("failed to sign repository")
+            .write(&metadata_dir)
+            .await
+            .expect("failed to write repository");
+
+        root.buffer().clone()
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn trust_root_custom_repository(cache_dir: TempDir) {
+        let repo_dir = TempDir::new().expect("cannot create temp repository dir");
+        let root = tuf_repository(repo_dir.path()).await;
+        let dir_url = |name| {
+            Url::from_directory_path(repo_dir.path().join(name)).expect("invalid directory URL")
+        };
+        let builder = || {
+            SigstoreTrustRoot::builder()
+                .with_metadata_base(dir_url("metadata"))
+                .with_targets_base(dir_url("targets"))
+                .with_root(root.clone())
+        };
+
+        let trust_root = builder()
+            .with_cache_dir(cache_dir.path())
+            .build()
+            .await
+            .expect("failed to construct SigstoreTrustRoot");
+        verify(&trust_root, Some(cache_dir.path()));
+        assert!(trust_root.signing_config().is_none());
+
+        let trust_root = builder()
+            .with_signing_config_target("signing_config.json")
+            .build()
+            .await
+            .expect("failed to construct SigstoreTrustRoot");
+        let ca = trust_root.signing_config().and_then(|config| config.ca());
+        assert_eq!(
+            ca.map(|ca| ca.url.as_str()),
+            Some("https://fulcio.example.com/")
+        );
+
+        let missing = builder()
+            .with_trusted_root_target("missing.json")
+            .build()
+            .await;
+        assert!(matches!(
+            missing,
+            Err(SigstoreError::TufTargetNotFoundError(_))
+        ));
+    }
+
+    #[rstest]
+    fn trust_root_from_file(cache_dir: TempDir) {
+        let trusted_root_path = cache_dir.path().join("trusted_root.json");
+        let data =
+            constants::static_resource("trusted_root.json").expect("missing embedded trusted root");
+        fs::write(&trusted_root_path, data).expect("failed to write trusted root");
+
+        let root = SigstoreTrustRoot::from_file(&trusted_root_path)
+            .expect("failed to load trusted root from file");
+        verify(&root, None);
+        assert!(root.rekor_log_keys().is_ok_and(|v| !v.is_empty()));
+
+        assert!(SigstoreTrustRoot::from_trusted_root_json(b"not a trusted root").is_err());
+    }
+
+    #[test]
+    fn validity_period_rejects_unrepresentable_bounds() {
+        let now = SystemTime::now();
+        let mut range = TimeRange {
+            start: Some(now.into()),
+            end: Some(now.into()),
+        };
+
+        let period = validity_period(Some(&range), "test").expect("failed to convert range");
+        assert!(period.start.is_some() && period.end.is_some());
+        assert_eq!(
+            validity_period(None, "test").expect("failed to convert range"),
+            ValidityPeriod::default()
+        );
+
+        if let Some(end) = range.end.as_mut() {
+            end.seconds = i64::MAX;
+        }
+        assert!(validity_period(Some(&range), "test").is_err());
+    }
+
     #[test]
     fn test_is_timerange_valid() {
         fn range_from(start: i64, end: i64) -> TimeRange {
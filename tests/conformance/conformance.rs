@@ -21,7 +21,7 @@ use std::{fs, process::exit};
 use clap::{Parser, Subcommand};
 use sigstore::{
     bundle::sign::SigningContext,
-    bundle::verify::{blocking::Verifier, policy},
+    bundle::verify::{blocking::Verifier, policy, VerificationOptions},
     oauth::IdentityToken,
 };
 
@@ -167,7 +167,10 @@ fn verify_bundle(args: VerifyBundle) -> anyhow::Result<()> {
         &mut artifact,
         bundle,
         &policy::Identity::new(certificate_identity, certificate_oidc_issuer),
-        true,
+        &VerificationOptions {
+            offline: true,
+            ..Default::default()
+        },
     )?;
 
     Ok(())